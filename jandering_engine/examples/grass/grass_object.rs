@@ -43,7 +43,11 @@ impl Renderable for GrassObject {
         self.indices.len() as u32
     }
 
-    fn get_buffers(&self) -> (BufferHandle, BufferHandle, Option<BufferHandle>) {
-        (self.vertex_buffer, self.index_buffer, None)
+    fn get_vertex_buffers(&self) -> Vec<BufferHandle> {
+        vec![self.vertex_buffer]
+    }
+
+    fn get_index_buffer(&self) -> BufferHandle {
+        self.index_buffer
     }
 }
@@ -1,16 +1,14 @@
 use grass_object::GrassObject;
 use image::GenericImageView;
 use jandering_engine::{
-    bind_group::{
-        BindGroup, BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutDescriptorEntry,
-        BindGroupLayoutEntry,
-    },
+    bind_group::BindGroup,
     engine::Engine,
     object::{Instance, Object, Vertex},
     render_pass::RenderPass,
     renderer::{BufferHandle, Janderer, Renderer},
-    shader::{ShaderDescriptor, ShaderSource},
-    texture::{sampler::SamplerDescriptor, texture_usage, TextureDescriptor, TextureFormat},
+    shader::{layout::ShaderType, ShaderDescriptor, ShaderSource, VertexLayout},
+    surface::SurfaceConfig,
+    texture::{sampler::SamplerDescriptor, TextureDescriptor},
     types::{UVec2, Vec3},
     utils::{
         free_camera::{FreeCameraController, MatrixCamera},
@@ -37,9 +35,7 @@ const GRASS_LOD2_N: u32 = (GRASS_LOD2_AREA * GRASS_DENSITY) as u32;
 
 const MULTISAMPLE: u32 = 4;
 
-#[repr(C)]
-#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
-
+#[derive(Debug, Copy, Clone, ShaderType)]
 pub struct RenderDataData {
     ground_color: Vec3,
     time: f32,
@@ -57,32 +53,16 @@ pub struct RenderDataData {
     terrain_size: f32,
     render_square_size: f32,
     fov_x: f32,
-    padding: [f32; 2],
 }
 
+#[derive(BindGroup)]
 pub struct RenderDataBindGroup {
     pub data: RenderDataData,
 
+    #[uniform]
     pub buffer_handle: BufferHandle,
 }
 
-impl BindGroup for RenderDataBindGroup {
-    fn get_layout(&self) -> BindGroupLayout {
-        BindGroupLayout {
-            entries: vec![BindGroupLayoutEntry::Data(self.buffer_handle)],
-        }
-    }
-
-    fn get_layout_descriptor() -> jandering_engine::bind_group::BindGroupLayoutDescriptor
-    where
-        Self: Sized,
-    {
-        BindGroupLayoutDescriptor {
-            entries: vec![BindGroupLayoutDescriptorEntry::Data { is_uniform: true }],
-        }
-    }
-}
-
 impl RenderDataBindGroup {
     fn new(renderer: &mut Renderer) -> Self {
         let data = RenderDataData {
@@ -102,10 +82,9 @@ impl RenderDataBindGroup {
             terrain_size: 1000.0,
             render_square_size: 100.0,
             fov_x: 45.0,
-            padding: [0.0; 2],
         };
 
-        let buffer_handle = renderer.create_uniform_buffer(bytemuck::cast_slice(&[data]));
+        let buffer_handle = renderer.create_std140_uniform_buffer(&data);
 
         Self {
             data,
@@ -118,7 +97,7 @@ const CAMERA_FOV: f32 = 45.0;
 const CAMEREA_NEAR: f32 = 0.01;
 const CAMEREA_FAR: f32 = 100000.0;
 fn main() {
-    let mut engine = pollster::block_on(Engine::default());
+    let mut engine = pollster::block_on(Engine::default()).unwrap();
 
     let mut window = engine.spawn_window(
         // also registers window by itself
@@ -158,66 +137,54 @@ fn main() {
     });
     let noise_texture = TextureBindGroup::new(renderer, noise_handle, tex_sampler);
 
-    let multisample_texture = renderer.create_texture(TextureDescriptor {
-        size: (1920, 1080).into(),
-        sample_count: MULTISAMPLE,
-        usage: texture_usage::TARGET,
-        ..Default::default()
-    });
-
-    let depth_texture = renderer.create_texture(TextureDescriptor {
-        size: (1920, 1080).into(),
-        sample_count: MULTISAMPLE,
-        format: TextureFormat::Depth32F,
-        usage: texture_usage::TARGET | texture_usage::BIND,
-        ..Default::default()
-    });
-
-    let grass_shader = renderer.create_shader(ShaderDescriptor {
-        source: ShaderSource::Code(include_str!("terrain_shader.wgsl").to_string()),
-        descriptors: vec![Vertex::desc()],
-        bind_group_layout_descriptors: vec![
-            camera.get_layout_descriptor(),
-            render_data_bind_group_layout.clone(),
-            TextureBindGroup::get_layout_descriptor(),
-            TextureBindGroup::get_layout_descriptor(),
-        ],
-        vs_entry: "fs_grass",
-        fs_entry: "vs_grass",
-        backface_culling: false,
-        depth: true,
-        multisample: MULTISAMPLE,
-        ..Default::default()
-    });
-
-    let ground_shader = renderer.create_shader(ShaderDescriptor {
-        source: ShaderSource::Code(include_str!("terrain_shader.wgsl").to_string()),
-        descriptors: vec![Vertex::desc(), Instance::desc()],
-        bind_group_layout_descriptors: vec![
-            camera.get_layout_descriptor(),
-            render_data_bind_group_layout.clone(),
-            TextureBindGroup::get_layout_descriptor(),
-        ],
-        vs_entry: "fs_ground",
-        fs_entry: "fs_ground",
-        backface_culling: false,
-        depth: true,
-        multisample: MULTISAMPLE,
-        ..Default::default()
-    });
-
-    let star_shader = renderer.create_shader(ShaderDescriptor {
-        source: ShaderSource::Code(include_str!("star_shader.wgsl").to_string()),
-        descriptors: vec![Vertex::desc()],
-        bind_group_layout_descriptors: vec![
-            camera.get_layout_descriptor(),
-            render_data_bind_group_layout.clone(),
-        ],
-        backface_culling: true,
-        depth: false,
-        multisample: MULTISAMPLE,
-        ..Default::default()
-    });
+    let grass_shader = renderer
+        .create_shader(ShaderDescriptor {
+            source: ShaderSource::Code(include_str!("terrain_shader.wgsl").to_string()),
+            descriptors: vec![Vertex::desc()],
+            bind_group_layout_descriptors: vec![
+                camera.get_layout_descriptor(),
+                render_data_bind_group_layout.clone(),
+                TextureBindGroup::get_layout_descriptor(),
+                TextureBindGroup::get_layout_descriptor(),
+            ],
+            vs_entry: "fs_grass".into(),
+            fs_entry: "vs_grass".into(),
+            backface_culling: false,
+            depth: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+    let ground_shader = renderer
+        .create_shader(ShaderDescriptor {
+            source: ShaderSource::Code(include_str!("terrain_shader.wgsl").to_string()),
+            descriptors: vec![Vertex::desc(), Instance::desc()],
+            bind_group_layout_descriptors: vec![
+                camera.get_layout_descriptor(),
+                render_data_bind_group_layout.clone(),
+                TextureBindGroup::get_layout_descriptor(),
+            ],
+            vs_entry: "fs_ground".into(),
+            fs_entry: "fs_ground".into(),
+            backface_culling: false,
+            depth: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+    let star_shader = renderer
+        .create_shader(ShaderDescriptor {
+            source: ShaderSource::Code(include_str!("star_shader.wgsl").to_string()),
+            descriptors: vec![Vertex::desc()],
+            bind_group_layout_descriptors: vec![
+                camera.get_layout_descriptor(),
+                render_data_bind_group_layout.clone(),
+            ],
+            backface_culling: true,
+            depth: false,
+            ..Default::default()
+        })
+        .unwrap();
 
     let render_data_handle = renderer.create_typed_bind_group(render_data);
 
@@ -227,11 +194,13 @@ fn main() {
     let grass_lod1 = GrassObject::from_text(include_str!("grass_lod1.obj"), renderer, GRASS_LOD1_N);
     let grass_lod2 = GrassObject::from_text(include_str!("grass_lod2.obj"), renderer, GRASS_LOD2_N);
 
-    let ground = Object::from_obj(
-        include_str!("plane.obj"),
-        // include_str!("grass_lod1.obj"),
+    let ground = Object::plane(
         renderer,
-        vec![Instance::default().scale(1000.0)],
+        8,
+        vec![Instance::default()
+            .rotate(90.0f32.to_radians(), Vec3::X)
+            .translate(Vec3::new(-500.0, 0.0, -500.0))
+            .scale(1000.0)],
     );
 
     let star_triangle = StarObject::new(renderer, STAR_COUNT);
@@ -251,9 +220,19 @@ fn main() {
         let events = window.events().clone();
 
         for event in events.iter() {
-            match event {
-                WindowEvent::WindowInitialized => renderer.register_window(&window),
-                _ => {}
+            if let WindowEvent::WindowInitialized = event {
+                renderer.register_window(&window).unwrap();
+
+                let surface_config = renderer.get_surface_config(&window);
+                renderer
+                    .set_surface_config(
+                        &window,
+                        SurfaceConfig {
+                            msaa_samples: MULTISAMPLE,
+                            ..surface_config
+                        },
+                    )
+                    .unwrap();
             }
         }
 
@@ -295,31 +274,12 @@ fn main() {
                 CAMEREA_NEAR,
                 CAMEREA_FAR,
             );
-
-            renderer.re_create_texture(
-                TextureDescriptor {
-                    size,
-                    format: TextureFormat::Depth32F,
-                    sample_count: MULTISAMPLE,
-                    usage: texture_usage::TARGET | texture_usage::BIND,
-                    ..Default::default()
-                },
-                depth_texture,
-            );
-
-            renderer.re_create_texture(
-                TextureDescriptor {
-                    size,
-                    sample_count: MULTISAMPLE,
-                    usage: texture_usage::TARGET,
-                    ..Default::default()
-                },
-                multisample_texture,
-            );
         }
 
         if events.is_pressed(Key::B) {
-            renderer.reload_shaders();
+            if let Err(err) = renderer.reload_shaders() {
+                println!("{err}");
+            }
         }
 
         if window.is_initialized() {
@@ -331,22 +291,15 @@ fn main() {
             render_data.data.sqrt_n_grass = (GRASS_LOD2_N as f32).sqrt() as u32;
             render_data.data.render_square_size = GRASS_LOD2_SIDE;
             let render_data = renderer.get_typed_bind_group(render_data_handle).unwrap();
-            renderer.write_buffer(
-                render_data.buffer_handle,
-                bytemuck::cast_slice(&[render_data.data]),
-            );
+            renderer.write_buffer(render_data.buffer_handle, &render_data.data.std140_bytes());
 
             let pass = RenderPass::new(&mut window)
                 .set_shader(star_shader)
-                .with_target_texture_resolve(
-                    jandering_engine::renderer::TargetTexture::Handle(multisample_texture),
-                    None,
-                )
-                .with_depth(depth_texture, Some(1.0))
                 .with_clear_color(sky_color.x, sky_color.y, sky_color.z)
                 .bind(0, camera.bind_group())
                 .bind(1, render_data_handle.into())
                 .render(&[&star_triangle])
+                .with_screen_depth(Some(1.0))
                 .set_shader(ground_shader)
                 .bind(2, heightmap_texture.into())
                 .render(&[&ground])
@@ -361,18 +314,11 @@ fn main() {
             render_data.data.sqrt_n_grass = (GRASS_LOD1_N as f32).sqrt() as u32;
             render_data.data.render_square_size = GRASS_LOD1_SIDE;
             let render_data = renderer.get_typed_bind_group(render_data_handle).unwrap();
-            renderer.write_buffer(
-                render_data.buffer_handle,
-                bytemuck::cast_slice(&[render_data.data]),
-            );
+            renderer.write_buffer(render_data.buffer_handle, &render_data.data.std140_bytes());
 
             let pass = RenderPass::new(&mut window)
                 .set_shader(grass_shader)
-                .with_depth(depth_texture, None)
-                .with_target_texture_resolve(
-                    jandering_engine::renderer::TargetTexture::Handle(multisample_texture),
-                    None,
-                )
+                .with_screen_depth(None)
                 .bind(0, camera.bind_group())
                 .bind(1, render_data_handle.into())
                 .bind(2, heightmap_texture.into())
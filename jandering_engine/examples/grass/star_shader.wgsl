@@ -1,9 +1,4 @@
-struct Camera {
-    up: vec4<f32>,
-    right: vec4<f32>,
-    view_pos: vec4<f32>,
-    view_proj: mat4x4<f32>
-};
+#include "jandering_engine/camera.wgsl"
 
 @group(0) @binding(0)
 var<uniform> camera: Camera;
@@ -58,12 +53,12 @@ fn vs_main(
 ) -> VertexOutput{
     var state = instance_index;
     let random_vec = normalize(vec3<f32>(random_float(&state), random_float(&state), random_float(&state)) * 2.0 - 1.0) * 1000.0;
-    let position = vec4<f32>(random_vec + camera.view_pos.xyz, 1.0);
+    let position = vec4<f32>(random_vec + camera.position.xyz, 1.0);
     // let position = vec4<f32>(random_vec, 1.0);
     let random_size = random_float(&state);
     let size = 5.0 * (1.0 + random_size * random_size);
 
-    let world_pos = position + camera.right * vertex.position.x * size + camera.up * vertex.position.y * size;
+    let world_pos = position + vec4<f32>(camera.right * vertex.position.x * size + camera.up * vertex.position.y * size, 0.0);
     
     var out: VertexOutput;
     out.clip_position = camera.view_proj * world_pos;
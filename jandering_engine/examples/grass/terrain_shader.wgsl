@@ -1,9 +1,4 @@
-struct Camera {
-    up: vec4<f32>,
-    right: vec4<f32>,
-    view_pos: vec4<f32>,
-    view_proj: mat4x4<f32>
-};
+#include "jandering_engine/camera.wgsl"
 
 @group(0) @binding(0)
 var<uniform> camera: Camera;
@@ -65,7 +60,7 @@ fn vs_grass(
     var v_pos = model.position;
     var v_normal = model.normal;
 
-    let camera_pos_t = camera.view_pos.xz / render_data.render_square_size;
+    let camera_pos_t = camera.position.xz / render_data.render_square_size;
 
     var grid_pos_u = vec2<u32>(instance_index % render_data.sqrt_n_grass, instance_index / render_data.sqrt_n_grass);
     var grid_pos = vec2<i32>(grid_pos_u);
@@ -133,7 +128,7 @@ fn fs_grass(in: GrassVertexOutput, @builtin(front_facing) front_facing: bool) ->
 
     let shaded = get_phong_color(in.pos, normal, color, 1.0);
     
-    let dist = 1.0 - abs(camera.view_pos.xz - in.pos.xz) / (render_data.render_square_size * 0.5);
+    let dist = 1.0 - abs(camera.position.xz - in.pos.xz) / (render_data.render_square_size * 0.5);
     var alpha = min(dist.x, dist.y);
     alpha = 1.0 - pow(1.0 - alpha, 5.0);
 
@@ -224,7 +219,7 @@ fn fs_ground(in: GroundVertexOutput) -> @location(0) vec4<f32>{
 
     let shaded = get_phong_color(in.world_pos, vec3<f32>(0.0, 1.0, 0.0), color, 0.0);
 
-    let dist = 1.0 - abs(camera.view_pos.xz - in.world_pos.xz) / (render_data.terrain_size * 0.5);
+    let dist = 1.0 - abs(camera.position.xz - in.world_pos.xz) / (render_data.terrain_size * 0.5);
     var alpha = min(dist.x, dist.y);
     alpha = 1.0 - pow(1.0 - alpha, 5.0);
 
@@ -256,7 +251,7 @@ fn calculate_wind(coords: vec2<f32>) -> f32{
 
 fn get_phong_color(world_pos: vec3<f32>, normal: vec3<f32>, color: vec3<f32>, shading_strength: f32) -> vec3<f32>{
     // Set up variables
-    let view_dir = normalize(camera.view_pos.xyz - world_pos);
+    let view_dir = normalize(camera.position.xyz - world_pos);
     let light_dir = -view_dir;
     // let light_dir = normalize(vec3<f32>(1.0, 1.0, 1.0));
     let light_color = 0.7;
@@ -1,12 +1,10 @@
 use jandering_engine::{
-    bind_group::{
-        BindGroup, BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutDescriptorEntry,
-        BindGroupLayoutEntry, SamplerType,
-    },
+    bind_group::BindGroup,
     renderer::{
         BindGroupHandle, BufferHandle, Janderer, Renderer, SamplerHandle, TextureHandle,
         UntypedBindGroupHandle,
     },
+    shader::layout::DataLayout,
     texture::{sampler::SamplerDescriptor, texture_usage, TextureDescriptor, TextureFormat},
     types::{Mat4, UVec2, Vec3},
     utils::free_camera::OPENGL_TO_WGPU_MATRIX,
@@ -16,7 +14,7 @@ const LIGHT_RESOLUTION: u32 = 2048;
 const LIGHT_UP: Vec3 = Vec3::Y;
 
 #[repr(C)]
-#[derive(Copy, Clone, bytemuck::Zeroable, bytemuck::Pod)]
+#[derive(Copy, Clone, bytemuck::Zeroable, bytemuck::Pod, DataLayout)]
 struct Data {
     up: Vec3,
     up_padding: f32,
@@ -40,68 +38,22 @@ pub struct Light {
     bind_group: BindGroupHandle<LightBindGroup>,
 }
 
+#[derive(BindGroup)]
 struct LightDataBindGroup {
+    #[uniform]
     pub buffer_handle: BufferHandle,
 }
 
+#[derive(BindGroup)]
 struct LightBindGroup {
+    #[uniform]
     pub buffer_handle: BufferHandle,
+    #[texture(depth)]
     pub texture_handle: TextureHandle,
+    #[sampler(non_filtering)]
     pub sampler_handle: SamplerHandle,
 }
 
-impl BindGroup for LightDataBindGroup {
-    fn get_layout(&self) -> BindGroupLayout {
-        BindGroupLayout {
-            entries: vec![BindGroupLayoutEntry::Data(self.buffer_handle)],
-        }
-    }
-
-    fn get_layout_descriptor() -> jandering_engine::bind_group::BindGroupLayoutDescriptor
-    where
-        Self: Sized,
-    {
-        BindGroupLayoutDescriptor {
-            entries: vec![BindGroupLayoutDescriptorEntry::Data { is_uniform: true }],
-        }
-    }
-}
-
-impl BindGroup for LightBindGroup {
-    fn get_layout(&self) -> BindGroupLayout {
-        BindGroupLayout {
-            entries: vec![
-                BindGroupLayoutEntry::Data(self.buffer_handle),
-                BindGroupLayoutEntry::Texture {
-                    handle: self.texture_handle,
-                    sample_type: jandering_engine::bind_group::TextureSampleType::Depth,
-                },
-                BindGroupLayoutEntry::Sampler {
-                    handle: self.sampler_handle,
-                    sampler_type: SamplerType::NonFiltering,
-                },
-            ],
-        }
-    }
-
-    fn get_layout_descriptor() -> jandering_engine::bind_group::BindGroupLayoutDescriptor
-    where
-        Self: Sized,
-    {
-        BindGroupLayoutDescriptor {
-            entries: vec![
-                BindGroupLayoutDescriptorEntry::Data { is_uniform: true },
-                BindGroupLayoutDescriptorEntry::Texture {
-                    sample_type: jandering_engine::bind_group::TextureSampleType::Depth,
-                },
-                BindGroupLayoutDescriptorEntry::Sampler {
-                    sampler_type: SamplerType::NonFiltering,
-                },
-            ],
-        }
-    }
-}
-
 impl Light {
     pub fn cone(renderer: &mut Renderer, fov: f32, position: Vec3, direction: Vec3) -> Self {
         let right = LIGHT_UP.cross(direction).normalize();
@@ -133,9 +85,9 @@ impl Light {
             padding: 0.0,
         };
 
-        let buffer_handle = renderer.create_uniform_buffer(bytemuck::cast_slice(&[data]));
+        let buffer_handle = renderer.create_typed_uniform_buffer(&data);
         let texture_handle = renderer.create_texture(TextureDescriptor {
-            size: UVec2::splat(LIGHT_RESOLUTION),
+            size: UVec2::splat(LIGHT_RESOLUTION).into(),
             format: TextureFormat::Depth32F,
             usage: texture_usage::ALL,
             ..Default::default()
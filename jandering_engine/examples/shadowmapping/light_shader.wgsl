@@ -11,23 +11,8 @@ struct Light {
 @group(0) @binding(0)
 var<uniform> light: Light;
 
-struct VertexInput{
-    @location(0) position: vec3<f32>,
-    @location(1) normal: vec3<f32>,
-    @location(2) uv: vec2<f32>,
-};
-
-struct InstanceInput{
-    @location(5) model_matrix_0: vec4<f32>,
-    @location(6) model_matrix_1: vec4<f32>,
-    @location(7) model_matrix_2: vec4<f32>,
-    @location(8) model_matrix_3: vec4<f32>,
-
-    @location(9)  inv_model_matrix_0: vec4<f32>,
-    @location(10) inv_model_matrix_1: vec4<f32>,
-    @location(11) inv_model_matrix_2: vec4<f32>,
-    @location(12) inv_model_matrix_3: vec4<f32>,
-}
+#include "jandering_engine/vertex.wgsl"
+#include "jandering_engine/instance.wgsl"
 
 struct VertexOutput{
     @builtin(position) clip_position: vec4<f32>,
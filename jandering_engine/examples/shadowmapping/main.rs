@@ -5,10 +5,10 @@ use jandering_engine::{
     object::{Instance, Object, Vertex},
     render_pass::RenderPass,
     renderer::{Janderer, Renderer, TargetTexture},
-    shader::ShaderDescriptor,
+    shader::{ShaderDescriptor, VertexLayout},
     texture::{
         texture_usage::{self},
-        TextureDescriptor, TextureFormat,
+        TextureDescriptor, TextureFormat, TextureSize,
     },
     types::{UVec2, Vec2, Vec3},
     utils::free_camera::MatrixCamera,
@@ -102,7 +102,7 @@ impl Ship {
 }
 
 fn main() {
-    let mut engine = pollster::block_on(Engine::default());
+    let mut engine = pollster::block_on(Engine::default()).unwrap();
 
     let mut window = engine.spawn_window(
         WindowConfig::default()
@@ -117,7 +117,7 @@ fn main() {
     let renderer = &mut engine.renderer;
 
     let depth_texture = renderer.create_texture(TextureDescriptor {
-        size: UVec2::splat(512),
+        size: TextureSize::Screen(window.id(), 1.0),
         format: TextureFormat::Depth32F,
         usage: texture_usage::ALL,
         ..Default::default()
@@ -140,42 +140,48 @@ fn main() {
     // .unwrap();
     let light_shader_source = include_str!("light_shader.wgsl").to_string();
 
-    let shader = renderer.create_shader(ShaderDescriptor {
-        source: jandering_engine::shader::ShaderSource::Code(shader_source.clone()),
-        descriptors: vec![Vertex::desc(), Instance::desc()],
-        bind_group_layout_descriptors: vec![
-            Light::get_layout_descriptor(),
-            camera.get_layout_descriptor(),
-        ],
-        backface_culling: false,
-        depth: true,
-        ..Default::default()
-    });
-
-    let light_shader = renderer.create_shader(ShaderDescriptor {
-        source: jandering_engine::shader::ShaderSource::Code(light_shader_source.clone()),
-        fs_entry: "fs_main",
-        descriptors: vec![Vertex::desc(), Instance::desc()],
-        bind_group_layout_descriptors: vec![
-            Light::get_data_only_layout_descriptor(),
-            camera.get_layout_descriptor(),
-        ],
-        depth: true,
-        backface_culling: true,
-        target_texture_format: None,
-        ..Default::default()
-    });
-
-    let popr_shader = renderer.create_shader(ShaderDescriptor {
-        source: jandering_engine::shader::ShaderSource::Code(
-            include_str!("popr_shader.wgsl").to_string(),
-        ),
-        descriptors: vec![Vertex::desc(), Instance::desc()],
-        bind_group_layout_descriptors: vec![Light::get_layout_descriptor()],
-        depth: false,
-        backface_culling: false,
-        ..Default::default()
-    });
+    let shader = renderer
+        .create_shader(ShaderDescriptor {
+            source: jandering_engine::shader::ShaderSource::Code(shader_source.clone()),
+            descriptors: vec![Vertex::desc(), Instance::desc()],
+            bind_group_layout_descriptors: vec![
+                Light::get_layout_descriptor(),
+                camera.get_layout_descriptor(),
+            ],
+            backface_culling: false,
+            depth: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+    let light_shader = renderer
+        .create_shader(ShaderDescriptor {
+            source: jandering_engine::shader::ShaderSource::Code(light_shader_source.clone()),
+            fs_entry: "fs_main".into(),
+            descriptors: vec![Vertex::desc(), Instance::desc()],
+            bind_group_layout_descriptors: vec![
+                Light::get_data_only_layout_descriptor(),
+                camera.get_layout_descriptor(),
+            ],
+            depth: true,
+            backface_culling: true,
+            target_texture_format: None,
+            ..Default::default()
+        })
+        .unwrap();
+
+    let popr_shader = renderer
+        .create_shader(ShaderDescriptor {
+            source: jandering_engine::shader::ShaderSource::Code(
+                include_str!("popr_shader.wgsl").to_string(),
+            ),
+            descriptors: vec![Vertex::desc(), Instance::desc()],
+            bind_group_layout_descriptors: vec![Light::get_layout_descriptor()],
+            depth: false,
+            backface_culling: false,
+            ..Default::default()
+        })
+        .unwrap();
 
     // let light_pos = Vec3::new(-70.0, 60.0, -70.0);
     let light_pos = Vec3::new(50.0, 100.0, 50.0);
@@ -216,7 +222,7 @@ fn main() {
 
         for event in events.iter() {
             match event {
-                WindowEvent::WindowInitialized => renderer.register_window(&window),
+                WindowEvent::WindowInitialized => renderer.register_window(&window).unwrap(),
                 WindowEvent::Resized(resolution) => {
                     let aspect_ratio = resolution.0 as f32 / resolution.1 as f32;
 
@@ -229,15 +235,6 @@ fn main() {
                         CAMERA_FAR,
                     );
                     // camera.make_perspective(40.0, aspect_ratio, CAMERA_NEAR, CAMERA_FAR);
-                    renderer.re_create_texture(
-                        TextureDescriptor {
-                            size: window.size().into(),
-                            format: TextureFormat::Depth32F,
-                            usage: texture_usage::ALL,
-                            ..Default::default()
-                        },
-                        depth_texture,
-                    );
                 }
                 WindowEvent::MouseMotion(position) => {
                     mouse_position = (*position).into();
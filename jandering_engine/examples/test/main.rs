@@ -1,14 +1,14 @@
 use jandering_engine::{
     engine::Engine,
-    object::{Instance, Object, Vertex},
+    object::{Instance, Object},
     render_pass::RenderPass,
     renderer::Janderer,
     shader::ShaderDescriptor,
     texture::{
         texture_usage::{self},
-        TextureDescriptor, TextureFormat,
+        TextureDescriptor, TextureFormat, TextureSize,
     },
-    types::{UVec2, Vec3},
+    types::Vec3,
     utils::free_camera::{FreeCameraController, MatrixCamera},
     window::{WindowConfig, WindowEvent, WindowManagerTrait, WindowTrait},
 };
@@ -18,9 +18,7 @@ const CAMEREA_NEAR: f32 = 0.01;
 const CAMEREA_FAR: f32 = 100000.0;
 
 fn main() {
-    let mut engine = pollster::block_on(Engine::default());
-
-    let resolution = 512;
+    let mut engine = pollster::block_on(Engine::default()).unwrap();
 
     let mut window = engine.spawn_window(
         // also registers window by itself
@@ -38,13 +36,16 @@ fn main() {
     let mut camera = MatrixCamera::with_controller(renderer, FreeCameraController::default());
     camera.make_perspective(CAMERA_FOV, 1.0, CAMEREA_NEAR, CAMEREA_FAR);
 
-    let shader = renderer.create_shader(ShaderDescriptor {
-        descriptors: vec![Vertex::desc(), Instance::desc()],
-        bind_group_layout_descriptors: vec![camera.get_layout_descriptor()],
-        backface_culling: true,
-        depth: true,
-        ..Default::default()
-    });
+    // layouts are read from the shader
+    let shader = renderer
+        .create_shader(ShaderDescriptor {
+            reflect: true,
+            instance_arguments: vec!["instance".into()],
+            backface_culling: true,
+            depth: true,
+            ..Default::default()
+        })
+        .unwrap();
 
     const COUNT: i32 = 1;
     let mut cube_instance_grid = Vec::new();
@@ -61,7 +62,7 @@ fn main() {
     let mut cube = Object::from_obj(include_str!("cube.obj"), renderer, cube_instance_grid);
 
     let depth_texture = renderer.create_texture(TextureDescriptor {
-        size: UVec2::splat(resolution),
+        size: TextureSize::Screen(window.id(), 1.0),
         format: TextureFormat::Depth32F,
         usage: texture_usage::ALL,
         ..Default::default()
@@ -76,9 +77,8 @@ fn main() {
         window.poll_events();
 
         for event in window.events().iter() {
-            match event {
-                WindowEvent::WindowInitialized => renderer.register_window(&window),
-                _ => {}
+            if let WindowEvent::WindowInitialized = event {
+                renderer.register_window(&window).unwrap();
             }
         }
 
@@ -98,15 +98,6 @@ fn main() {
                 CAMEREA_NEAR,
                 CAMEREA_FAR,
             );
-            renderer.re_create_texture(
-                TextureDescriptor {
-                    size: window.size().into(),
-                    format: TextureFormat::Depth32F,
-                    usage: texture_usage::ALL,
-                    ..Default::default()
-                },
-                depth_texture,
-            );
         }
 
         cube.instances.iter_mut().for_each(|e| {
@@ -4,6 +4,8 @@ use crate::renderer::{BufferHandle, BufferType};
 
 use super::renderer::{SamplerHandle, TextureHandle};
 
+pub use jandering_engine_derive::BindGroup;
+
 pub trait BindGroup: Any + BindGroupToAny {
     fn get_layout_descriptor() -> BindGroupLayoutDescriptor
     where
@@ -26,22 +28,25 @@ impl<T: 'static> BindGroupToAny for T {
     }
 }
 
-#[derive(Clone, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 pub enum TextureSampleType {
     #[default]
     Filterable,
     NonFilterable,
     Depth,
+    // texture_2d<u32> and texture_2d<i32>
+    Uint,
+    Sint,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum BindGroupLayoutDescriptorEntry {
-    Data { is_uniform: bool },
+    Data { is_uniform: bool, read_only: bool },
     Texture { sample_type: TextureSampleType },
     Sampler { sampler_type: SamplerType },
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct BindGroupLayoutDescriptor {
     pub entries: Vec<BindGroupLayoutDescriptorEntry>,
 }
@@ -49,6 +54,8 @@ pub struct BindGroupLayoutDescriptor {
 #[derive(Clone)]
 pub enum BindGroupLayoutEntry {
     Data(BufferHandle),
+    // storage buffer the shaders can only read from
+    ReadOnlyData(BufferHandle),
     Texture {
         handle: TextureHandle,
         sample_type: TextureSampleType,
@@ -64,6 +71,11 @@ impl From<BindGroupLayoutEntry> for BindGroupLayoutDescriptorEntry {
         match val {
             BindGroupLayoutEntry::Data(buffer_handle) => BindGroupLayoutDescriptorEntry::Data {
                 is_uniform: matches!(buffer_handle.buffer_type, BufferType::Uniform),
+                read_only: false,
+            },
+            BindGroupLayoutEntry::ReadOnlyData(_) => BindGroupLayoutDescriptorEntry::Data {
+                is_uniform: false,
+                read_only: true,
             },
             BindGroupLayoutEntry::Texture { sample_type, .. } => {
                 BindGroupLayoutDescriptorEntry::Texture { sample_type }
@@ -80,9 +92,124 @@ pub struct BindGroupLayout {
     pub entries: Vec<BindGroupLayoutEntry>,
 }
 
-#[derive(Clone)]
+impl From<&BindGroupLayout> for BindGroupLayoutDescriptor {
+    fn from(val: &BindGroupLayout) -> Self {
+        BindGroupLayoutDescriptor {
+            entries: val.entries.iter().cloned().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum SamplerType {
     Filtering,
     NonFiltering,
     Comparison,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::{BufferHandle, SamplerHandle, TextureHandle};
+
+    #[derive(BindGroup)]
+    struct Everything {
+        // fields without a binding attribute aren't part of the layout
+        _data: f32,
+        #[uniform]
+        uniform: BufferHandle,
+        #[storage]
+        storage: BufferHandle,
+        #[storage(read_only)]
+        read_only: BufferHandle,
+        #[texture]
+        texture: TextureHandle,
+        #[texture(depth)]
+        depth: TextureHandle,
+        #[texture(uint)]
+        uint: TextureHandle,
+        #[sampler]
+        sampler: SamplerHandle,
+        #[sampler(comparison)]
+        comparison: SamplerHandle,
+    }
+
+    fn everything() -> Everything {
+        Everything {
+            _data: 0.0,
+            uniform: BufferHandle::uniform(0),
+            storage: BufferHandle::storage(1),
+            read_only: BufferHandle::storage(2),
+            texture: TextureHandle(3),
+            depth: TextureHandle(4),
+            uint: TextureHandle(5),
+            sampler: SamplerHandle(6),
+            comparison: SamplerHandle(7),
+        }
+    }
+
+    #[test]
+    fn layout_descriptor_follows_the_attributes() {
+        let data = |is_uniform, read_only| BindGroupLayoutDescriptorEntry::Data {
+            is_uniform,
+            read_only,
+        };
+        let texture = |sample_type| BindGroupLayoutDescriptorEntry::Texture { sample_type };
+        let sampler = |sampler_type| BindGroupLayoutDescriptorEntry::Sampler { sampler_type };
+
+        assert_eq!(
+            Everything::get_layout_descriptor().entries,
+            [
+                data(true, false),
+                data(false, false),
+                data(false, true),
+                texture(TextureSampleType::Filterable),
+                texture(TextureSampleType::Depth),
+                texture(TextureSampleType::Uint),
+                sampler(SamplerType::Filtering),
+                sampler(SamplerType::Comparison),
+            ]
+        );
+    }
+
+    #[test]
+    fn layout_holds_the_fields_in_order() {
+        let layout = everything().get_layout();
+        assert_eq!(layout.entries.len(), 8);
+
+        let handles = layout
+            .entries
+            .iter()
+            .map(|e| match e {
+                BindGroupLayoutEntry::Data(handle) => ("data", handle.index),
+                BindGroupLayoutEntry::ReadOnlyData(handle) => ("read only", handle.index),
+                BindGroupLayoutEntry::Texture { handle, .. } => ("texture", handle.0),
+                BindGroupLayoutEntry::Sampler { handle, .. } => ("sampler", handle.0),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            handles,
+            [
+                ("data", 0),
+                ("data", 1),
+                ("read only", 2),
+                ("texture", 3),
+                ("texture", 4),
+                ("texture", 5),
+                ("sampler", 6),
+                ("sampler", 7),
+            ]
+        );
+    }
+
+    #[test]
+    fn layout_matches_its_descriptor() {
+        let layout = everything().get_layout();
+        let descriptor = layout
+            .entries
+            .into_iter()
+            .map(BindGroupLayoutDescriptorEntry::from)
+            .collect::<Vec<_>>();
+        assert_eq!(descriptor, Everything::get_layout_descriptor().entries);
+    }
+}
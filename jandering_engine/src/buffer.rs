@@ -0,0 +1,60 @@
+pub mod buffer_usage {
+    pub type BufferUsage = u32;
+
+    pub const NONE: BufferUsage = 0;
+
+    pub const COPY_SRC: BufferUsage = 1 << 0;
+    pub const COPY_TARGET: BufferUsage = 1 << 1;
+    pub const UNIFORM: BufferUsage = 1 << 2;
+    pub const STORAGE: BufferUsage = 1 << 3;
+    pub const VERTEX: BufferUsage = 1 << 4;
+    pub const INDEX: BufferUsage = 1 << 5;
+    pub const INDIRECT: BufferUsage = 1 << 6;
+    pub const MAP_READ: BufferUsage = 1 << 7;
+    pub const MAP_WRITE: BufferUsage = 1 << 8;
+}
+
+#[derive(Clone)]
+pub struct BufferDescriptor<'data> {
+    pub label: &'static str,
+    // if contents are longer than size the buffer is sized to fit them
+    pub size: usize,
+    pub contents: Option<&'data [u8]>,
+    pub usage: buffer_usage::BufferUsage,
+}
+
+impl<'data> Default for BufferDescriptor<'data> {
+    fn default() -> Self {
+        Self {
+            label: "buffer",
+            size: 0,
+            contents: None,
+            usage: buffer_usage::UNIFORM | buffer_usage::COPY_TARGET,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum BufferError {
+    // bind groups type a buffer by how it's bound, which has to be one way
+    UniformAndStorage,
+    // mappable buffers can only be copied into (MAP_READ) or out of (MAP_WRITE)
+    MapUsage(buffer_usage::BufferUsage),
+}
+
+impl std::fmt::Display for BufferError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BufferError::UniformAndStorage => write!(
+                f,
+                "buffers can't be both UNIFORM and STORAGE, bind groups wouldn't know which one it is"
+            ),
+            BufferError::MapUsage(usage) => write!(
+                f,
+                "usage {usage:#b} maps the buffer, MAP_READ only goes with COPY_TARGET and MAP_WRITE only with COPY_SRC"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BufferError {}
@@ -1,21 +1,61 @@
 use std::collections::HashMap;
 
-use crate::renderer::{ComputeShaderHandle, UntypedBindGroupHandle};
+use crate::renderer::{BufferHandle, ComputeShaderHandle, UntypedBindGroupHandle};
+
+#[derive(Clone, Default, Debug)]
+pub enum ComputeAction {
+    Dispatch {
+        x: u32,
+        y: u32,
+        z: u32,
+    },
+    DispatchIndirect {
+        buffer: BufferHandle,
+        offset: u64,
+    },
+    #[default]
+    Empty,
+}
+
+#[derive(Clone, Debug)]
+pub struct ComputeStep {
+    pub(crate) action: ComputeAction,
 
-pub struct ComputePassData {
     pub(crate) shader: ComputeShaderHandle,
     pub(crate) bind_groups: HashMap<u32, UntypedBindGroupHandle>,
 }
 
-impl Default for ComputePassData {
+impl Default for ComputeStep {
     fn default() -> Self {
         Self {
+            action: Default::default(),
             shader: ComputeShaderHandle(0),
             bind_groups: Default::default(),
         }
     }
 }
 
+pub struct ComputePassData {
+    pub(crate) steps: Vec<ComputeStep>,
+}
+
+impl Default for ComputePassData {
+    fn default() -> Self {
+        Self {
+            steps: vec![ComputeStep::default()],
+        }
+    }
+}
+
+impl ComputePassData {
+    fn push_action(&mut self, action: ComputeAction) {
+        let step = self.steps.last_mut().unwrap();
+        step.action = action;
+        let next_step = step.clone();
+        self.steps.push(next_step);
+    }
+}
+
 pub trait ComputePassTrait {
     fn get_data(&mut self) -> &mut ComputePassData;
 
@@ -24,6 +64,9 @@ pub trait ComputePassTrait {
         Self: Sized,
     {
         self.get_data()
+            .steps
+            .last_mut()
+            .unwrap()
             .bind_groups
             .entry(slot)
             .and_modify(|e| *e = bind_group)
@@ -35,17 +78,42 @@ pub trait ComputePassTrait {
     where
         Self: Sized,
     {
-        self.get_data().bind_groups.remove(&slot);
+        self.get_data()
+            .steps
+            .last_mut()
+            .unwrap()
+            .bind_groups
+            .remove(&slot);
         self
     }
 
-    fn dispatch(self, x: u32, y: u32, z: u32);
-
     fn set_shader(mut self, shader: ComputeShaderHandle) -> Self
     where
         Self: Sized,
     {
-        self.get_data().shader = shader;
+        self.get_data().steps.last_mut().unwrap().shader = shader;
+        self
+    }
+
+    // records a dispatch, nothing runs until the pass is submitted
+    fn dispatch(mut self, x: u32, y: u32, z: u32) -> Self
+    where
+        Self: Sized,
+    {
+        self.get_data()
+            .push_action(ComputeAction::Dispatch { x, y, z });
         self
     }
+
+    // buffer has to hold three u32 workgroup counts at offset
+    fn dispatch_indirect(mut self, buffer: BufferHandle, offset: u64) -> Self
+    where
+        Self: Sized,
+    {
+        self.get_data()
+            .push_action(ComputeAction::DispatchIndirect { buffer, offset });
+        self
+    }
+
+    fn submit(self);
 }
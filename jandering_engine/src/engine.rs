@@ -1,10 +1,13 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use crate::renderer::Janderer;
+use crate::{
+    renderer::Janderer,
+    surface::{SurfaceCapabilities, SurfaceConfig},
+};
 
 use super::renderer::Renderer;
 
-use je_windowing::{Window, WindowConfig, WindowManager, WindowManagerTrait};
+use je_windowing::{Window, WindowConfig, WindowId, WindowManager, WindowManagerTrait};
 use notify::{event::ModifyKind, EventKind};
 
 pub struct Engine {
@@ -13,7 +16,7 @@ pub struct Engine {
 }
 
 impl Engine {
-    pub async fn default() -> Self {
+    pub async fn default() -> Result<Self, EngineError> {
         Self::new(EngineConfig::default()).await
     }
 }
@@ -21,17 +24,93 @@ impl Engine {
 #[derive(Default)]
 pub struct EngineConfig {
     pub enable_compute: bool,
+    // compiled pipelines are kept here between runs, only on drivers that support it (vulkan)
+    pub pipeline_cache_dir: Option<PathBuf>,
+    pub backends: wgpu::Backends,
+    // HighPerformance picks the discrete gpu on laptops
+    pub power_preference: wgpu::PowerPreference,
+    // the software adapter, for ci machines without a gpu
+    pub force_fallback_adapter: bool,
+    // index into Engine::adapters(backends), overrides the two options above
+    pub adapter: Option<usize>,
+    // on top of the ones the engine needs itself
+    pub features: wgpu::Features,
+    // None uses the defaults, or webgl2's on the web
+    pub limits: Option<wgpu::Limits>,
+    // checks every render pass against the shaders it uses before encoding it,
+    // None only does in debug builds
+    pub validate_passes: Option<bool>,
 }
 
+#[derive(Debug)]
+pub enum EngineError {
+    NoAdapter,
+    AdapterIndex {
+        index: usize,
+        count: usize,
+    },
+    MissingFeatures(wgpu::Features),
+    Device(wgpu::RequestDeviceError),
+    UnsupportedSurfaceConfig {
+        config: SurfaceConfig,
+        capabilities: SurfaceCapabilities,
+    },
+    CreateSurface(wgpu::CreateSurfaceError),
+    // the surface has none of the formats render targets can have
+    UnsupportedSurfaceFormats(Vec<wgpu::TextureFormat>),
+    UnregisteredWindow(WindowId),
+}
+
+impl std::fmt::Display for EngineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EngineError::NoAdapter => write!(f, "no adapter matches the engine config"),
+            EngineError::AdapterIndex { index, count } => {
+                write!(
+                    f,
+                    "adapter {index} was asked for but there are only {count}"
+                )
+            }
+            EngineError::MissingFeatures(features) => {
+                write!(f, "the adapter doesn't support {features:?}")
+            }
+            EngineError::Device(err) => write!(f, "couldn't create the device: {err}"),
+            EngineError::UnsupportedSurfaceConfig {
+                config,
+                capabilities,
+            } => write!(
+                f,
+                "the surface doesn't support {config:?}, it supports {capabilities:?}"
+            ),
+            EngineError::CreateSurface(err) => write!(f, "couldn't create the surface: {err}"),
+            EngineError::UnsupportedSurfaceFormats(formats) => write!(
+                f,
+                "the surface only supports {formats:?}, none of which the engine can draw to"
+            ),
+            EngineError::UnregisteredWindow(window) => {
+                write!(f, "window {window} isn't registered with the renderer")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
+
 impl Engine {
-    pub async fn new(config: EngineConfig) -> Self {
-        let renderer = Renderer::new(config).await;
+    pub async fn new(config: EngineConfig) -> Result<Self, EngineError> {
+        let renderer = Renderer::new(config).await?;
         let window_manager = WindowManager::new();
 
-        Self {
+        Ok(Self {
             renderer,
             window_manager,
-        }
+        })
+    }
+
+    // what EngineConfig::adapter indexes into
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn adapters(backends: wgpu::Backends) -> Vec<wgpu::AdapterInfo> {
+        Renderer::adapters(backends)
     }
 
     pub fn spawn_window(&mut self, window_config: WindowConfig) -> Window {
@@ -81,6 +160,10 @@ impl Engine {
                 })
                 .collect::<Vec<_>>();
 
+            for window in window_manager.take_closed_windows() {
+                renderer.unregister_window(window);
+            }
+
             function(&mut renderer, window_manager, &events);
             renderer.present();
         });
@@ -93,6 +176,10 @@ impl Engine {
         } = self;
 
         window_manager.run(move |window_manager| {
+            for window in window_manager.take_closed_windows() {
+                renderer.unregister_window(window);
+            }
+
             function(&mut renderer, window_manager);
             renderer.present();
         });
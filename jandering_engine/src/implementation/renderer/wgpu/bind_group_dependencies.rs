@@ -0,0 +1,102 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::bind_group::{BindGroupLayout, BindGroupLayoutEntry};
+
+use super::WGPURenderer;
+
+// what a wgpu bind group holds on to, by index
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(super) enum BoundResource {
+    Buffer(usize),
+    Texture(usize),
+    Sampler(usize),
+}
+
+impl BoundResource {
+    fn of(layout: &BindGroupLayout) -> Vec<Self> {
+        layout
+            .entries
+            .iter()
+            .map(|e| match e {
+                BindGroupLayoutEntry::Data(handle) | BindGroupLayoutEntry::ReadOnlyData(handle) => {
+                    Self::Buffer(handle.index)
+                }
+                BindGroupLayoutEntry::Texture { handle, .. } => Self::Texture(handle.0),
+                BindGroupLayoutEntry::Sampler { handle, .. } => Self::Sampler(handle.0),
+            })
+            .collect()
+    }
+}
+
+#[derive(Default)]
+pub(super) struct BindGroupDependencies {
+    // what each bind group was last built with
+    resources: Vec<Vec<BoundResource>>,
+    users: HashMap<BoundResource, HashSet<usize>>,
+    // a resource they use was recreated
+    stale: BTreeSet<usize>,
+    // borrowed mutably, rebuilt only if they point at other resources now
+    touched: BTreeSet<usize>,
+}
+
+impl BindGroupDependencies {
+    fn set(&mut self, bind_group: usize, resources: Vec<BoundResource>) {
+        if bind_group >= self.resources.len() {
+            self.resources.resize(bind_group + 1, Vec::new());
+        }
+
+        for resource in std::mem::take(&mut self.resources[bind_group]) {
+            if let Some(users) = self.users.get_mut(&resource) {
+                users.remove(&bind_group);
+            }
+        }
+        for resource in resources.iter() {
+            self.users.entry(*resource).or_default().insert(bind_group);
+        }
+        self.resources[bind_group] = resources;
+
+        self.stale.remove(&bind_group);
+        self.touched.remove(&bind_group);
+    }
+
+    pub(super) fn resource_changed(&mut self, resource: BoundResource) {
+        if let Some(users) = self.users.get(&resource) {
+            self.stale.extend(users.iter().copied());
+        }
+    }
+
+    pub(super) fn touch(&mut self, bind_group: usize) {
+        self.touched.insert(bind_group);
+    }
+}
+
+impl WGPURenderer {
+    pub(super) fn rebuild_bind_group(&mut self, index: usize) {
+        let layout = self.bind_groups[index].get_layout();
+        let data = self.create_bind_group_render_data(&layout);
+        if index >= self.bind_groups_render_data.len() {
+            self.bind_groups_render_data.push(data);
+        } else {
+            self.bind_groups_render_data[index] = data;
+        }
+        self.bind_group_dependencies
+            .set(index, BoundResource::of(&layout));
+    }
+
+    // runs before passes are encoded, so they never see a bind group holding old resources
+    pub(super) fn rebuild_dirty_bind_groups(&mut self) {
+        let dependencies = &mut self.bind_group_dependencies;
+        let stale = std::mem::take(&mut dependencies.stale);
+        let touched = std::mem::take(&mut dependencies.touched);
+
+        for index in stale.union(&touched) {
+            if !stale.contains(index) {
+                let layout = self.bind_groups[*index].get_layout();
+                if BoundResource::of(&layout) == self.bind_group_dependencies.resources[*index] {
+                    continue;
+                }
+            }
+            self.rebuild_bind_group(*index);
+        }
+    }
+}
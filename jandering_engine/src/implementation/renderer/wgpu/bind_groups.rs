@@ -6,10 +6,10 @@ use crate::{
         BindGroupLayoutEntry,
     },
     renderer::{BindGroupHandle, UntypedBindGroupHandle},
-    shader::BufferLayout,
+    shader::{BufferLayout, BufferLayoutEntryDataType},
 };
 
-use super::WGPURenderer;
+use super::{WGPUBindGroupRenderData, WGPURenderer};
 
 impl WGPURenderer {
     pub fn get_layout_descriptor<T: Into<BindGroupLayoutDescriptorEntry> + Clone>(
@@ -21,12 +21,15 @@ impl WGPURenderer {
             .enumerate()
             .map(
                 |(i, e)| match Into::<BindGroupLayoutDescriptorEntry>::into(e.clone()) {
-                    BindGroupLayoutDescriptorEntry::Data { is_uniform, .. } => {
+                    BindGroupLayoutDescriptorEntry::Data {
+                        is_uniform,
+                        read_only,
+                    } => {
                         let ty = wgpu::BindingType::Buffer {
                             ty: if is_uniform {
                                 wgpu::BufferBindingType::Uniform
                             } else {
-                                wgpu::BufferBindingType::Storage { read_only: false }
+                                wgpu::BufferBindingType::Storage { read_only }
                             },
                             has_dynamic_offset: false,
                             min_binding_size: None,
@@ -58,6 +61,12 @@ impl WGPURenderer {
                                     crate::bind_group::TextureSampleType::Depth => {
                                         wgpu::TextureSampleType::Depth
                                     }
+                                    crate::bind_group::TextureSampleType::Uint => {
+                                        wgpu::TextureSampleType::Uint
+                                    }
+                                    crate::bind_group::TextureSampleType::Sint => {
+                                        wgpu::TextureSampleType::Sint
+                                    }
                                 },
                                 view_dimension: wgpu::TextureViewDimension::D2,
                                 multisampled: false,
@@ -95,30 +104,52 @@ impl WGPURenderer {
         })
     }
 
-    pub fn get_layout_descriptors(
-        device: &wgpu::Device,
-        layouts: &[BindGroupLayoutDescriptor],
-    ) -> Vec<wgpu::BindGroupLayout> {
-        layouts
+    // layouts are cached by descriptor so equal layouts share a single wgpu object
+    pub(crate) fn cache_bind_group_layout(&mut self, descriptor: &BindGroupLayoutDescriptor) {
+        if !self.bind_group_layouts.contains_key(descriptor) {
+            let layout = Self::get_layout_descriptor(&self.device, &descriptor.entries);
+            self.bind_group_layouts.insert(descriptor.clone(), layout);
+        }
+    }
+
+    pub(crate) fn cache_pipeline_layout(&mut self, descriptors: &[BindGroupLayoutDescriptor]) {
+        if self.pipeline_layouts.contains_key(descriptors) {
+            return;
+        }
+
+        for descriptor in descriptors {
+            self.cache_bind_group_layout(descriptor);
+        }
+
+        let bind_group_layouts = descriptors
             .iter()
-            .map(|e| Self::get_layout_descriptor(device, &e.entries))
-            .collect()
+            .map(|e| &self.bind_group_layouts[e])
+            .collect::<Vec<_>>();
+
+        let layout = self
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Pipeline Layout"),
+                bind_group_layouts: &bind_group_layouts,
+                push_constant_ranges: &[],
+            });
+
+        self.pipeline_layouts.insert(descriptors.to_vec(), layout);
     }
 
-    pub fn get_layout_and_entries<'a>(
-        &'a self,
-        device: &'a wgpu::Device,
+    pub fn get_bind_group_entries(
+        &self,
         layout: &BindGroupLayout,
-    ) -> (wgpu::BindGroupLayout, Vec<wgpu::BindGroupEntry<'a>>) {
-        let bind_group_layout = Self::get_layout_descriptor(device, &layout.entries);
-        let entries = layout
+    ) -> Vec<wgpu::BindGroupEntry<'_>> {
+        layout
             .entries
             .iter()
             .enumerate()
             .map(|(i, entry)| wgpu::BindGroupEntry {
                 binding: i as u32,
                 resource: match entry {
-                    BindGroupLayoutEntry::Data(handle) => {
+                    BindGroupLayoutEntry::Data(handle)
+                    | BindGroupLayoutEntry::ReadOnlyData(handle) => {
                         self.buffers[handle.index].as_entire_binding()
                     }
                     BindGroupLayoutEntry::Texture { handle, .. } => {
@@ -129,35 +160,75 @@ impl WGPURenderer {
                     }
                 },
             })
-            .collect::<Vec<_>>();
+            .collect()
+    }
+
+    pub(super) fn create_bind_group_render_data(
+        &mut self,
+        layout: &BindGroupLayout,
+    ) -> WGPUBindGroupRenderData {
+        let descriptor = BindGroupLayoutDescriptor::from(layout);
+        self.cache_bind_group_layout(&descriptor);
+
+        let bind_group_layout = &self.bind_group_layouts[&descriptor];
+        let entries = self.get_bind_group_entries(layout);
+
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: bind_group_layout,
+            entries: &entries[..],
+            label: Some("bind_group"),
+        });
 
-        (bind_group_layout, entries)
+        WGPUBindGroupRenderData { bind_group }
+    }
+
+    pub fn get_vertex_format(data_type: BufferLayoutEntryDataType) -> wgpu::VertexFormat {
+        match data_type {
+            BufferLayoutEntryDataType::Float32 => wgpu::VertexFormat::Float32,
+            BufferLayoutEntryDataType::Float32x2 => wgpu::VertexFormat::Float32x2,
+            BufferLayoutEntryDataType::Float32x3 => wgpu::VertexFormat::Float32x3,
+            BufferLayoutEntryDataType::Float32x4 => wgpu::VertexFormat::Float32x4,
+            BufferLayoutEntryDataType::U32 => wgpu::VertexFormat::Uint32,
+            BufferLayoutEntryDataType::Uint32x2 => wgpu::VertexFormat::Uint32x2,
+            BufferLayoutEntryDataType::Uint32x3 => wgpu::VertexFormat::Uint32x3,
+            BufferLayoutEntryDataType::Uint32x4 => wgpu::VertexFormat::Uint32x4,
+            BufferLayoutEntryDataType::Sint32 => wgpu::VertexFormat::Sint32,
+            BufferLayoutEntryDataType::Sint32x2 => wgpu::VertexFormat::Sint32x2,
+            BufferLayoutEntryDataType::Sint32x3 => wgpu::VertexFormat::Sint32x3,
+            BufferLayoutEntryDataType::Sint32x4 => wgpu::VertexFormat::Sint32x4,
+            BufferLayoutEntryDataType::Uint8x2 => wgpu::VertexFormat::Uint8x2,
+            BufferLayoutEntryDataType::Uint8x4 => wgpu::VertexFormat::Uint8x4,
+            BufferLayoutEntryDataType::Sint8x2 => wgpu::VertexFormat::Sint8x2,
+            BufferLayoutEntryDataType::Sint8x4 => wgpu::VertexFormat::Sint8x4,
+            BufferLayoutEntryDataType::Unorm8x2 => wgpu::VertexFormat::Unorm8x2,
+            BufferLayoutEntryDataType::Unorm8x4 => wgpu::VertexFormat::Unorm8x4,
+            BufferLayoutEntryDataType::Snorm8x2 => wgpu::VertexFormat::Snorm8x2,
+            BufferLayoutEntryDataType::Snorm8x4 => wgpu::VertexFormat::Snorm8x4,
+            BufferLayoutEntryDataType::Uint16x2 => wgpu::VertexFormat::Uint16x2,
+            BufferLayoutEntryDataType::Uint16x4 => wgpu::VertexFormat::Uint16x4,
+            BufferLayoutEntryDataType::Sint16x2 => wgpu::VertexFormat::Sint16x2,
+            BufferLayoutEntryDataType::Sint16x4 => wgpu::VertexFormat::Sint16x4,
+            BufferLayoutEntryDataType::Unorm16x2 => wgpu::VertexFormat::Unorm16x2,
+            BufferLayoutEntryDataType::Unorm16x4 => wgpu::VertexFormat::Unorm16x4,
+            BufferLayoutEntryDataType::Snorm16x2 => wgpu::VertexFormat::Snorm16x2,
+            BufferLayoutEntryDataType::Snorm16x4 => wgpu::VertexFormat::Snorm16x4,
+            BufferLayoutEntryDataType::Float16x2 => wgpu::VertexFormat::Float16x2,
+            BufferLayoutEntryDataType::Float16x4 => wgpu::VertexFormat::Float16x4,
+            BufferLayoutEntryDataType::Unorm10_10_10_2 => wgpu::VertexFormat::Unorm10_10_10_2,
+        }
     }
 
     pub fn get_buffer_attributes(layout: &BufferLayout) -> Vec<VertexAttribute> {
         let mut entries = Vec::new();
         let mut offset = 0;
         for entry in layout.entries.iter() {
+            let entry_offset = entry.offset.unwrap_or(offset);
             entries.push(wgpu::VertexAttribute {
-                format: match entry.data_type {
-                    crate::shader::BufferLayoutEntryDataType::Float32 => {
-                        wgpu::VertexFormat::Float32
-                    }
-                    crate::shader::BufferLayoutEntryDataType::Float32x2 => {
-                        wgpu::VertexFormat::Float32x2
-                    }
-                    crate::shader::BufferLayoutEntryDataType::Float32x3 => {
-                        wgpu::VertexFormat::Float32x3
-                    }
-                    crate::shader::BufferLayoutEntryDataType::Float32x4 => {
-                        wgpu::VertexFormat::Float32x4
-                    }
-                    crate::shader::BufferLayoutEntryDataType::U32 => wgpu::VertexFormat::Uint32,
-                },
-                offset,
+                format: Self::get_vertex_format(entry.data_type),
+                offset: entry_offset,
                 shader_location: entry.location,
             });
-            offset += entry.data_type.size_bytes();
+            offset = entry_offset + entry.data_type.size_bytes();
         }
         entries
     }
@@ -169,32 +240,13 @@ impl WGPURenderer {
         layouts
             .iter()
             .enumerate()
-            .map(|(i, e)| {
-                if let Some(last) = entries[i].last() {
-                    let offset = last.offset
-                        + 4 * match last.format {
-                            wgpu::VertexFormat::Float32 => 1,
-                            wgpu::VertexFormat::Float32x2 => 2,
-                            wgpu::VertexFormat::Float32x3 => 3,
-                            wgpu::VertexFormat::Float32x4 => 4,
-                            _ => panic!(),
-                        };
-
-                    wgpu::VertexBufferLayout {
-                        array_stride: offset as wgpu::BufferAddress,
-                        step_mode: match e.step_mode {
-                            crate::shader::BufferLayoutStepMode::Vertex => {
-                                wgpu::VertexStepMode::Vertex
-                            }
-                            crate::shader::BufferLayoutStepMode::Instance => {
-                                wgpu::VertexStepMode::Instance
-                            }
-                        },
-                        attributes: &entries[i],
-                    }
-                } else {
-                    panic!()
-                }
+            .map(|(i, e)| wgpu::VertexBufferLayout {
+                array_stride: e.stride() as wgpu::BufferAddress,
+                step_mode: match e.step_mode {
+                    crate::shader::BufferLayoutStepMode::Vertex => wgpu::VertexStepMode::Vertex,
+                    crate::shader::BufferLayoutStepMode::Instance => wgpu::VertexStepMode::Instance,
+                },
+                attributes: &entries[i],
             })
             .collect()
     }
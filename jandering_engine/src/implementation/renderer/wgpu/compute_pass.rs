@@ -1,7 +1,10 @@
-use crate::compute_pass::{ComputePassData, ComputePassTrait};
+use std::collections::HashMap;
+
+use crate::compute_pass::{ComputeAction, ComputePassData, ComputePassTrait, ComputeStep};
 
 use super::WGPURenderer;
 
+#[must_use = "compute passes do nothing unless submitted"]
 pub struct WGPUComputePass<'renderer> {
     renderer: &'renderer mut WGPURenderer,
     data: ComputePassData,
@@ -22,7 +25,21 @@ impl<'renderer> ComputePassTrait for WGPUComputePass<'renderer> {
         &mut self.data
     }
 
-    fn dispatch(self, x: u32, y: u32, z: u32) {
+    fn submit(self) {
+        let ComputePassData { steps } = self.data;
+
+        self.renderer.rebuild_dirty_bind_groups();
+
+        // the last step is the placeholder the next action would've gone into
+        let len = steps.len() - 1;
+
+        if cfg!(debug_assertions) {
+            for step in steps.iter().take(len) {
+                self.renderer
+                    .check_compute_shader_data_layouts(step.shader, &step.bind_groups);
+            }
+        }
+
         let mut encoder =
             self.renderer
                 .device
@@ -36,18 +53,54 @@ impl<'renderer> ComputePassTrait for WGPUComputePass<'renderer> {
                 timestamp_writes: None,
             });
 
-            for (index, handle) in self.data.bind_groups.iter() {
-                compute_pass.set_bind_group(
-                    *index,
-                    &self.renderer.bind_groups_render_data[handle.0].bind_group,
-                    &[],
-                );
+            let mut bound_pipeline = None;
+            let mut bound_bind_groups = HashMap::new();
+
+            for step in steps.iter().take(len) {
+                let ComputeStep {
+                    action,
+                    shader,
+                    bind_groups,
+                } = step;
+
+                if matches!(action, ComputeAction::Empty) {
+                    continue;
+                }
+
+                if bound_pipeline != Some(*shader) {
+                    bound_pipeline = Some(*shader);
+                    self.renderer.frame_stats.pipeline_switches += 1;
+                }
+                let shader = &self.renderer.compute_shaders[shader.0];
+                compute_pass.set_pipeline(&shader.pipeline);
+
+                for (index, handle) in bind_groups.iter() {
+                    if bound_bind_groups.insert(*index, *handle) != Some(*handle) {
+                        self.renderer.frame_stats.bind_group_switches += 1;
+                    }
+                    compute_pass.set_bind_group(
+                        *index,
+                        &self.renderer.bind_groups_render_data[handle.0].bind_group,
+                        &[],
+                    );
+                }
+
+                match action {
+                    ComputeAction::Dispatch { x, y, z } => {
+                        compute_pass.dispatch_workgroups(*x, *y, *z)
+                    }
+                    ComputeAction::DispatchIndirect { buffer, offset } => compute_pass
+                        .dispatch_workgroups_indirect(
+                            &self.renderer.buffers[buffer.index],
+                            *offset,
+                        ),
+                    ComputeAction::Empty => {}
+                }
+                self.renderer.frame_stats.dispatches += 1;
             }
-            let shader = &self.renderer.compute_shaders[self.data.shader.0];
-            compute_pass.set_pipeline(&shader.pipeline);
-            compute_pass.dispatch_workgroups(x, y, z);
         }
 
         self.renderer.queue.submit(Some(encoder.finish()));
+        self.renderer.frame_stats.passes += 1;
     }
 }
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use crate::{
+    bind_group::BindGroupLayoutEntry,
+    renderer::{ComputeShaderHandle, ShaderHandle, ShaderVariant, UntypedBindGroupHandle},
+    shader::reflection::ShaderReflection,
+};
+
+use super::WGPURenderer;
+
+// a shader and the bind group set at one of its groups, each pair is only checked once
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) enum DataLayoutCheck {
+    Shader(usize, ShaderVariant, u32, usize),
+    Compute(usize, u32, usize),
+}
+
+impl WGPURenderer {
+    pub(super) fn check_shader_data_layouts(
+        &mut self,
+        shader: ShaderHandle,
+        variant: ShaderVariant,
+        bind_groups: &HashMap<u32, UntypedBindGroupHandle>,
+    ) {
+        for (group, bind_group) in bind_groups.iter() {
+            let check = DataLayoutCheck::Shader(shader.0, variant, *group, bind_group.0);
+            if !self.checked_data_layouts.insert(check) {
+                continue;
+            }
+
+            // only there when the shader reflects or passes are validated
+            let Some(reflection) = self.shaders[shader.0]
+                .compiled
+                .get(&variant)
+                .and_then(|e| e.reflection.as_ref())
+            else {
+                return;
+            };
+            if let Err(err) = self.check_bind_group_data(reflection, *group, *bind_group) {
+                panic!(
+                    "shader \"{}\": {}",
+                    self.shader_descriptors[shader.0].name, err
+                );
+            }
+        }
+    }
+
+    pub(super) fn check_compute_shader_data_layouts(
+        &mut self,
+        shader: ComputeShaderHandle,
+        bind_groups: &HashMap<u32, UntypedBindGroupHandle>,
+    ) {
+        for (group, bind_group) in bind_groups.iter() {
+            let check = DataLayoutCheck::Compute(shader.0, *group, bind_group.0);
+            if !self.checked_data_layouts.insert(check) {
+                continue;
+            }
+
+            let Some(reflection) = &self.compute_shaders[shader.0].reflection else {
+                return;
+            };
+            if let Err(err) = self.check_bind_group_data(reflection, *group, *bind_group) {
+                panic!(
+                    "compute shader \"{}\": {}",
+                    self.compute_shader_descriptors[shader.0].entry, err
+                );
+            }
+        }
+    }
+
+    // typed buffers in the bind group against the structs the shader reads them as
+    fn check_bind_group_data(
+        &self,
+        reflection: &ShaderReflection,
+        group: u32,
+        bind_group: UntypedBindGroupHandle,
+    ) -> Result<(), String> {
+        let Some(bind_group) = self.bind_groups.get(bind_group.0) else {
+            return Ok(());
+        };
+
+        for (binding, entry) in bind_group.get_layout().entries.iter().enumerate() {
+            let (BindGroupLayoutEntry::Data(buffer) | BindGroupLayoutEntry::ReadOnlyData(buffer)) =
+                entry
+            else {
+                continue;
+            };
+            let Some(layout) = self.buffer_data_layouts.get(&buffer.index) else {
+                continue;
+            };
+            let Some((expected, array)) = reflection.data_layout(group, binding as u32) else {
+                continue;
+            };
+
+            layout
+                .check(expected, array)
+                .map_err(|err| format!("@group({group}) @binding({binding}): {err}"))?;
+        }
+
+        Ok(())
+    }
+}
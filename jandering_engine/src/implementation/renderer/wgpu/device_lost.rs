@@ -0,0 +1,123 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use wgpu::DeviceLostReason;
+
+use crate::renderer::Janderer;
+
+use super::WGPURenderer;
+
+// runs once everything is recreated. buffers and textures come back zeroed, whatever was
+// written to them has to be uploaded again from here, Object::upload does it for objects
+pub(super) type DeviceLostCallback = Box<dyn FnMut(&mut WGPURenderer)>;
+
+// set from wgpu's callback, which can run on any thread
+pub(super) fn watch_device_lost(device: &wgpu::Device) -> Arc<AtomicBool> {
+    let lost = Arc::new(AtomicBool::new(false));
+    let flag = lost.clone();
+    device.set_device_lost_callback(move |reason, message| {
+        // the old device getting dropped during recovery isn't a loss
+        if matches!(
+            reason,
+            DeviceLostReason::Dropped | DeviceLostReason::ReplacedCallback
+        ) {
+            return;
+        }
+        log::error!("device lost ({reason:?}): {message}");
+        flag.store(true, Ordering::SeqCst);
+    });
+    lost
+}
+
+impl WGPURenderer {
+    // the flag is only set while the device is polled, present does that once a frame
+    pub(super) fn recover_lost_device(&mut self) {
+        if !self.device_lost.load(Ordering::SeqCst) {
+            return;
+        }
+
+        cfg_if::cfg_if! {
+            if #[cfg(target_arch = "wasm32")] {
+                panic!("device lost, recovering it isn't supported on the web");
+            } else {
+                self.recreate_device();
+            }
+        }
+    }
+
+    // everything gets recreated from what the renderer kept around, buffer contents and
+    // texture data are gone and have to be uploaded again by the device lost callback
+    #[cfg(not(target_arch = "wasm32"))]
+    fn recreate_device(&mut self) {
+        let (device, queue) =
+            pollster::block_on(self.adapter.request_device(&self.device_descriptor, None))
+                .unwrap_or_else(|err| panic!("device lost and couldn't be recreated: {err}"));
+        self.device_lost = watch_device_lost(&device);
+        self.device = device;
+        self.queue = queue;
+
+        self.bind_group_layouts.clear();
+        self.pipeline_layouts.clear();
+        self.checked_data_layouts.clear();
+
+        if let Some(pipeline_cache) = self.pipeline_cache.take() {
+            self.pipeline_cache = pipeline_cache.recreate(&self.device, &self.adapter);
+        }
+
+        for surface in self.surfaces.values_mut() {
+            surface.surface_texture = None;
+            surface.surface.configure(&self.device, &surface.config);
+            surface.drop_targets();
+        }
+
+        for (buffer, label) in self.buffers.iter_mut().zip(self.buffer_labels.iter()) {
+            *buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size: buffer.size(),
+                usage: buffer.usage(),
+                mapped_at_creation: false,
+            });
+        }
+
+        for (texture, label) in self.textures.iter_mut().zip(self.texture_labels.iter()) {
+            texture.texture = self.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: texture.texture.size(),
+                mip_level_count: texture.texture.mip_level_count(),
+                sample_count: texture.texture.sample_count(),
+                dimension: texture.texture.dimension(),
+                format: texture.texture.format(),
+                usage: texture.texture.usage(),
+                view_formats: &[],
+            });
+            texture.view = texture
+                .texture
+                .create_view(&wgpu::TextureViewDescriptor::default());
+        }
+
+        self.samplers = self
+            .sampler_descriptors
+            .iter()
+            .map(|e| self.device.create_sampler(e))
+            .collect();
+
+        // they compiled before the device was lost, only their files can have changed since
+        if let Err(err) = self.reload_shaders() {
+            panic!("recreating shaders after the device was lost, {err}");
+        }
+        if let Err(err) = self.re_create_compute_shaders() {
+            panic!("recreating compute shaders after the device was lost, {err}");
+        }
+
+        for i in 0..self.bind_groups.len() {
+            self.rebuild_bind_group(i);
+        }
+
+        if let Some(mut callback) = self.device_lost_callback.take() {
+            callback(self);
+            self.device_lost_callback = Some(callback);
+        }
+    }
+}
@@ -1,48 +1,103 @@
-use std::{collections::HashMap, marker::PhantomData};
+use std::{
+    borrow::Cow,
+    collections::{hash_map::Entry, HashMap, HashSet},
+    marker::PhantomData,
+    sync::{atomic::AtomicBool, Arc},
+};
 
 use compute_pass::WGPUComputePass;
-use wgpu::{util::DeviceExt, ComputePipelineDescriptor, Features, PresentMode, SurfaceTexture};
+use wgpu::{util::DeviceExt, ComputePipelineDescriptor, Features, SurfaceTexture};
 
 use crate::{
-    bind_group::BindGroup,
-    engine::EngineConfig,
+    bind_group::{BindGroup, BindGroupLayoutDescriptor},
+    buffer::{buffer_usage, BufferDescriptor, BufferError},
+    engine::{EngineConfig, EngineError},
     render_pass::{RenderPass, RenderStep},
     renderer::{
-        BindGroupHandle, BufferHandle, ComputeShaderHandle, Janderer, SamplerHandle, ShaderHandle,
-        TargetTexture, TextureHandle, UntypedBindGroupHandle,
+        BindGroupHandle, BufferHandle, BufferType, ComputeShaderHandle, Janderer, SamplerHandle,
+        ShaderHandle, ShaderVariant, TargetTexture, TextureHandle, UntypedBindGroupHandle,
+    },
+    shader::{
+        frontend::{CompiledShader, ShaderCompileError},
+        layout::{DataLayout, LayoutRules, ShaderType, StructLayout},
+        reflection::ShaderReflection,
+        ComputeShaderDescriptor, ShaderDescriptor,
     },
-    shader::{ComputeShaderDescriptor, ShaderDescriptor},
+    stats::{FrameStats, RendererStats},
+    surface::{SurfaceCapabilities, SurfaceConfig},
     texture::{
         sampler::{
             SamplerAddressMode, SamplerCompareFunction, SamplerDescriptor, SamplerFilterMode,
         },
-        texture_usage, Texture, TextureDescriptor, TextureFormat,
+        texture_usage, Texture, TextureDescriptor, TextureFormat, TextureSize,
     },
-    utils::load_text,
 };
 
-use je_windowing::{Window, WindowTrait};
+use je_windowing::{Window, WindowId, WindowTrait};
 
+mod bind_group_dependencies;
 mod bind_groups;
 pub mod compute_pass;
+mod data_layouts;
+mod device_lost;
+mod pipeline_cache;
+mod stats;
+mod surface;
+mod validation;
+
+use bind_group_dependencies::{BindGroupDependencies, BoundResource};
+use data_layouts::DataLayoutCheck;
+use device_lost::DeviceLostCallback;
+use pipeline_cache::PipelineCache;
 
 struct WGPUBindGroupRenderData {
     pub bind_group: wgpu::BindGroup,
 }
 
+#[derive(Default)]
 pub struct WGPUShader {
-    pub pipeline: wgpu::RenderPipeline,
+    // by variant and sample count
+    pub variants: HashMap<(ShaderVariant, u32), wgpu::RenderPipeline>,
+    compiled: HashMap<ShaderVariant, CompiledVariant>,
+}
+
+// a variant's source is compiled once, the pipelines for each sample count share it
+struct CompiledVariant {
+    modules: Vec<wgpu::ShaderModule>,
+    vs_entry: String,
+    fs_entry: String,
+    reflection: Option<ShaderReflection>,
+    // the descriptor with the layouts reflection filled in
+    resolved: ShaderDescriptor,
 }
 
 pub struct WGPUComputeShader {
     pub pipeline: wgpu::ComputePipeline,
+    reflection: Option<ShaderReflection>,
+}
+
+struct TransientBuffer {
+    handle: BufferHandle,
+    usage: buffer_usage::BufferUsage,
+    size: usize,
+    in_use: bool,
 }
 
 #[derive(Debug)]
 pub(crate) struct Surface {
+    // owns a SharedWindow, so the window outlives it
     surface: wgpu::Surface<'static>,
     config: wgpu::SurfaceConfiguration,
     surface_texture: Option<SurfaceTexture>,
+    // the present mode follows the window's preference whenever it changes
+    fps_preference: je_windowing::FpsPreference,
+    needs_configure: bool,
+    // set when acquiring failed, until the frame is presented
+    skip_frame: bool,
+    // hidden targets passes drawing to the screen use, made when first needed
+    msaa_samples: u32,
+    msaa_texture: Option<Texture>,
+    depth_texture: Option<Texture>,
 }
 
 pub struct WGPURenderer {
@@ -50,6 +105,10 @@ pub struct WGPURenderer {
     adapter: wgpu::Adapter,
     pub(crate) device: wgpu::Device,
     pub(crate) queue: wgpu::Queue,
+    // kept to request the device again after it's lost
+    device_descriptor: wgpu::DeviceDescriptor<'static>,
+    device_lost: Arc<AtomicBool>,
+    device_lost_callback: Option<DeviceLostCallback>,
 
     pub(crate) surfaces: HashMap<u32, Surface>,
 
@@ -61,36 +120,96 @@ pub struct WGPURenderer {
 
     bind_groups: Vec<Box<dyn BindGroup>>,
     bind_groups_render_data: Vec<WGPUBindGroupRenderData>,
+    bind_group_dependencies: BindGroupDependencies,
+
+    bind_group_layouts: HashMap<BindGroupLayoutDescriptor, wgpu::BindGroupLayout>,
+    pipeline_layouts: HashMap<Vec<BindGroupLayoutDescriptor>, wgpu::PipelineLayout>,
 
     pub(crate) textures: Vec<Texture>,
+    texture_labels: Vec<Cow<'static, str>>,
+    // textures sized relative to a window, by index
+    screen_textures: HashMap<usize, TextureDescriptor<'static>>,
     pub(crate) samplers: Vec<wgpu::Sampler>,
+    sampler_descriptors: Vec<wgpu::SamplerDescriptor<'static>>,
 
     pub(crate) buffers: Vec<wgpu::Buffer>,
+    buffer_labels: Vec<&'static str>,
+    transient_buffers: Vec<TransientBuffer>,
+
+    // only filled in debug builds, by buffer index
+    buffer_data_layouts: HashMap<usize, StructLayout>,
+    checked_data_layouts: HashSet<DataLayoutCheck>,
+
+    pipeline_cache: Option<PipelineCache>,
+
+    frame_stats: FrameStats,
+    last_frame_stats: FrameStats,
+
+    validate_passes: bool,
+}
+
+impl Drop for WGPURenderer {
+    fn drop(&mut self) {
+        if let Some(pipeline_cache) = &self.pipeline_cache {
+            pipeline_cache.save();
+        }
+    }
 }
 
 impl Janderer for WGPURenderer {
-    async fn new(config: EngineConfig) -> Self {
+    async fn new(config: EngineConfig) -> Result<Self, EngineError> {
         // inits wgpu
-        let instance = wgpu::Instance::default();
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: config.backends,
+            ..Default::default()
+        });
 
         // actual physical graphics card
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions::default())
-            .await
-            .unwrap();
-
-        let required_limits = if cfg!(target_arch = "wasm32") {
-            wgpu::Limits::downlevel_webgl2_defaults()
-        } else {
-            wgpu::Limits::default()
+        let adapter = match config.adapter {
+            #[cfg(not(target_arch = "wasm32"))]
+            Some(index) => {
+                let mut adapters = instance.enumerate_adapters(config.backends);
+                let count = adapters.len();
+                if index >= count {
+                    return Err(EngineError::AdapterIndex { index, count });
+                }
+                adapters.swap_remove(index)
+            }
+            #[cfg(target_arch = "wasm32")]
+            Some(index) => return Err(EngineError::AdapterIndex { index, count: 0 }),
+            None => instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: config.power_preference,
+                    force_fallback_adapter: config.force_fallback_adapter,
+                    compatible_surface: None,
+                })
+                .await
+                .ok_or(EngineError::NoAdapter)?,
         };
 
+        let required_limits = config.limits.clone().unwrap_or_else(|| {
+            if cfg!(target_arch = "wasm32") {
+                wgpu::Limits::downlevel_webgl2_defaults()
+            } else {
+                wgpu::Limits::default()
+            }
+        });
+
         let device_descriptor = {
-            let mut required_features = Features::empty();
+            let mut required_features = config.features;
             if config.enable_compute {
                 required_features.insert(Features::VERTEX_WRITABLE_STORAGE)
             };
-            &wgpu::DeviceDescriptor {
+
+            let missing = required_features - adapter.features();
+            if !missing.is_empty() {
+                return Err(EngineError::MissingFeatures(missing));
+            }
+
+            if config.pipeline_cache_dir.is_some() {
+                required_features.insert(adapter.features() & Features::PIPELINE_CACHE)
+            }
+            wgpu::DeviceDescriptor {
                 required_limits,
                 required_features,
                 ..Default::default()
@@ -99,20 +218,32 @@ impl Janderer for WGPURenderer {
 
         // device is logical graphics card and queue is used for executing command buffers
         let (device, queue) = adapter
-            .request_device(device_descriptor, None)
+            .request_device(&device_descriptor, None)
             .await
-            .unwrap();
+            .map_err(EngineError::Device)?;
+        let device_lost = device_lost::watch_device_lost(&device);
+
+        let pipeline_cache = config
+            .pipeline_cache_dir
+            .as_deref()
+            .and_then(|dir| PipelineCache::new(&device, &adapter, dir));
 
-        Self {
+        Ok(Self {
             instance,
             adapter,
             device,
             queue,
+            device_descriptor,
+            device_lost,
+            device_lost_callback: None,
 
             surfaces: HashMap::new(),
 
             textures: Vec::new(),
+            texture_labels: Vec::new(),
+            screen_textures: HashMap::new(),
             samplers: Vec::new(),
+            sampler_descriptors: Vec::new(),
 
             shaders: Vec::new(),
             shader_descriptors: Vec::new(),
@@ -122,56 +253,52 @@ impl Janderer for WGPURenderer {
 
             bind_groups: Vec::new(),
             bind_groups_render_data: Vec::new(),
+            bind_group_dependencies: BindGroupDependencies::default(),
 
-            buffers: Vec::new(),
-        }
-    }
+            bind_group_layouts: HashMap::new(),
+            pipeline_layouts: HashMap::new(),
 
-    fn register_window(&mut self, window: &Window) {
-        self.surfaces.entry(window.id()).or_insert({
-            let surface = self
-                .instance
-                .create_surface(unsafe { std::mem::transmute::<&Window, &'static Window>(window) })
-                .unwrap();
-            let config = {
-                let surface_capabilities = surface.get_capabilities(&self.adapter);
+            buffers: Vec::new(),
+            buffer_labels: Vec::new(),
+            transient_buffers: Vec::new(),
 
-                let surface_format = surface_capabilities
-                    .formats
-                    .iter()
-                    .copied()
-                    .find(|f| f.is_srgb())
-                    .unwrap_or(surface_capabilities.formats[0]);
+            buffer_data_layouts: HashMap::new(),
+            checked_data_layouts: HashSet::new(),
 
-                let present_mode = match window.get_fps_prefrence() {
-                    je_windowing::FpsPreference::Vsync => PresentMode::AutoVsync,
-                    je_windowing::FpsPreference::Exact(_)
-                    | je_windowing::FpsPreference::Uncapped => PresentMode::Immediate,
-                };
+            pipeline_cache,
 
-                let (width, height) = {
-                    let size = window.size();
-                    (size.0, size.1)
-                };
-                wgpu::SurfaceConfiguration {
-                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-                    format: surface_format,
-                    width,
-                    height,
-                    present_mode,
-                    alpha_mode: surface_capabilities.alpha_modes[0],
-                    view_formats: vec![],
-                    desired_maximum_frame_latency: 2,
-                }
-            };
+            frame_stats: FrameStats::default(),
+            last_frame_stats: FrameStats::default(),
+            validate_passes: config.validate_passes.unwrap_or(cfg!(debug_assertions)),
+        })
+    }
 
-            surface.configure(&self.device, &config);
-            Surface {
-                surface,
-                config,
-                surface_texture: None,
-            }
+    #[cfg(not(target_arch = "wasm32"))]
+    fn adapters(backends: wgpu::Backends) -> Vec<wgpu::AdapterInfo> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends,
+            ..Default::default()
         });
+        instance
+            .enumerate_adapters(backends)
+            .iter()
+            .map(|e| e.get_info())
+            .collect()
+    }
+
+    fn register_window(&mut self, window: &Window) -> Result<(), EngineError> {
+        if !self.surfaces.contains_key(&window.id()) {
+            let surface = Surface::new(self, window)?;
+            self.surfaces.insert(window.id(), surface);
+            self.resize_screen_textures();
+        }
+        Ok(())
+    }
+
+    fn unregister_window(&mut self, window: WindowId) {
+        if let Some(mut surface) = self.surfaces.remove(&window) {
+            surface.surface_texture = None;
+        }
     }
 
     fn resize(&mut self, window: &Window, width: u32, height: u32) {
@@ -188,82 +315,336 @@ impl Janderer for WGPURenderer {
             surface.config.height = height;
             surface.surface.configure(&self.device, &surface.config);
         });
+        self.resize_screen_textures();
     }
 
-    fn create_uniform_buffer(&mut self, contents: &[u8]) -> BufferHandle {
-        let buffer = self
-            .device
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: None,
-                contents,
-                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-            });
+    fn get_surface_config(&self, window: &Window) -> SurfaceConfig {
+        self.surfaces[&window.id()].config()
+    }
+
+    fn get_surface_capabilities(&self, window: &Window) -> SurfaceCapabilities {
+        self.surfaces[&window.id()].capabilities(&self.adapter, &self.device)
+    }
+
+    fn set_surface_config(
+        &mut self,
+        window: &Window,
+        config: SurfaceConfig,
+    ) -> Result<(), EngineError> {
+        let Some(surface) = self.surfaces.get_mut(&window.id()) else {
+            return Err(EngineError::UnregisteredWindow(window.id()));
+        };
+        surface.set_config(&self.adapter, &self.device, config)?;
+
+        // shaders drawing to the screen get the new sample count now instead of mid-frame
+        let format = surface.config.format;
+        let samples = surface.msaa_samples;
+        for i in 0..self.shaders.len() {
+            let target = self.shader_descriptors[i].target_texture_format;
+            if target.map(Self::target_format) != Some(format) {
+                continue;
+            }
+            let mut variants = Vec::new();
+            for (variant, _) in self.shaders[i].variants.keys() {
+                if !variants.contains(variant) {
+                    variants.push(*variant);
+                }
+            }
+            for variant in variants {
+                self.create_missing_pipeline(ShaderHandle(i), variant, samples)
+                    .unwrap_or_else(|err| {
+                        panic!("shader \"{}\": {err}", self.shader_descriptors[i].name)
+                    });
+            }
+        }
+        Ok(())
+    }
+
+    fn create_buffer(&mut self, desc: BufferDescriptor) -> Result<BufferHandle, BufferError> {
+        let buffer_type = BufferType::from_usage(desc.usage)?;
+
+        let mut usage = wgpu::BufferUsages::empty();
+        if desc.usage & buffer_usage::COPY_SRC != buffer_usage::NONE {
+            usage |= wgpu::BufferUsages::COPY_SRC;
+        }
+        if desc.usage & buffer_usage::COPY_TARGET != buffer_usage::NONE {
+            usage |= wgpu::BufferUsages::COPY_DST;
+        }
+        if desc.usage & buffer_usage::UNIFORM != buffer_usage::NONE {
+            usage |= wgpu::BufferUsages::UNIFORM;
+        }
+        if desc.usage & buffer_usage::STORAGE != buffer_usage::NONE {
+            usage |= wgpu::BufferUsages::STORAGE;
+        }
+        if desc.usage & buffer_usage::VERTEX != buffer_usage::NONE {
+            usage |= wgpu::BufferUsages::VERTEX;
+        }
+        if desc.usage & buffer_usage::INDEX != buffer_usage::NONE {
+            usage |= wgpu::BufferUsages::INDEX;
+        }
+        if desc.usage & buffer_usage::INDIRECT != buffer_usage::NONE {
+            usage |= wgpu::BufferUsages::INDIRECT;
+        }
+        if desc.usage & buffer_usage::MAP_READ != buffer_usage::NONE {
+            usage |= wgpu::BufferUsages::MAP_READ;
+        }
+        if desc.usage & buffer_usage::MAP_WRITE != buffer_usage::NONE {
+            usage |= wgpu::BufferUsages::MAP_WRITE;
+        }
+
+        let buffer = match desc.contents {
+            Some(contents) if contents.len() >= desc.size => {
+                self.device
+                    .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some(desc.label),
+                        contents,
+                        usage,
+                    })
+            }
+            Some(contents) => {
+                let mut padded = contents.to_vec();
+                padded.resize(desc.size, 0);
+                self.device
+                    .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some(desc.label),
+                        contents: &padded,
+                        usage,
+                    })
+            }
+            None => self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(desc.label),
+                size: desc.size as u64,
+                usage,
+                mapped_at_creation: false,
+            }),
+        };
 
         self.buffers.push(buffer);
-        BufferHandle::uniform(self.buffers.len() - 1)
+        self.buffer_labels.push(desc.label);
+        Ok(BufferHandle {
+            buffer_type,
+            index: self.buffers.len() - 1,
+        })
+    }
+
+    fn create_uniform_buffer(&mut self, contents: &[u8]) -> BufferHandle {
+        self.create_buffer(BufferDescriptor {
+            label: "uniform buffer",
+            contents: Some(contents),
+            usage: buffer_usage::UNIFORM | buffer_usage::COPY_SRC | buffer_usage::COPY_TARGET,
+            ..Default::default()
+        })
+        .unwrap()
     }
 
     fn create_storage_buffer(&mut self, contents: &[u8]) -> BufferHandle {
-        let buffer = self
-            .device
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: None,
-                contents,
-                usage: wgpu::BufferUsages::STORAGE,
-            });
+        self.create_buffer(BufferDescriptor {
+            label: "storage buffer",
+            contents: Some(contents),
+            usage: buffer_usage::STORAGE | buffer_usage::COPY_TARGET,
+            ..Default::default()
+        })
+        .unwrap()
+    }
 
-        self.buffers.push(buffer);
-        BufferHandle::storage(self.buffers.len() - 1)
+    fn create_typed_uniform_buffer<T: DataLayout + bytemuck::Pod>(
+        &mut self,
+        data: &T,
+    ) -> BufferHandle {
+        let handle = self.create_uniform_buffer(bytemuck::bytes_of(data));
+        if cfg!(debug_assertions) {
+            self.buffer_data_layouts
+                .insert(handle.index, T::data_layout());
+        }
+        handle
     }
 
-    fn create_storage_buffer_with_size(&mut self, size: usize) -> BufferHandle {
-        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
-            label: None,
-            size: size as u64,
-            usage: wgpu::BufferUsages::STORAGE,
-            mapped_at_creation: false,
-        });
+    fn create_typed_storage_buffer<T: DataLayout + bytemuck::Pod>(
+        &mut self,
+        data: &[T],
+    ) -> BufferHandle {
+        let handle = self.create_storage_buffer(bytemuck::cast_slice(data));
+        if cfg!(debug_assertions) {
+            self.buffer_data_layouts
+                .insert(handle.index, T::data_layout());
+        }
+        handle
+    }
 
-        self.buffers.push(buffer);
-        BufferHandle::storage(self.buffers.len() - 1)
+    fn create_std140_uniform_buffer<T: ShaderType>(&mut self, data: &T) -> BufferHandle {
+        let handle = self.create_uniform_buffer(&data.std140_bytes());
+        if cfg!(debug_assertions) {
+            self.buffer_data_layouts
+                .insert(handle.index, T::shader_layout(LayoutRules::Std140));
+        }
+        handle
     }
 
-    fn create_vertex_buffer(&mut self, contents: &[u8]) -> BufferHandle {
-        let buffer = self
-            .device
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: None,
-                contents,
-                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
-            });
+    fn create_storage_buffer_with_size(&mut self, size: usize) -> BufferHandle {
+        self.create_buffer(BufferDescriptor {
+            label: "storage buffer",
+            size,
+            usage: buffer_usage::STORAGE | buffer_usage::COPY_TARGET,
+            ..Default::default()
+        })
+        .unwrap()
+    }
 
-        self.buffers.push(buffer);
-        BufferHandle::uniform(self.buffers.len() - 1)
+    fn create_vertex_buffer(&mut self, contents: &[u8]) -> BufferHandle {
+        self.create_buffer(BufferDescriptor {
+            label: "vertex buffer",
+            contents: Some(contents),
+            usage: buffer_usage::VERTEX | buffer_usage::COPY_SRC | buffer_usage::COPY_TARGET,
+            ..Default::default()
+        })
+        .unwrap()
     }
 
     fn create_index_buffer(&mut self, contents: &[u8]) -> BufferHandle {
-        let buffer = self
-            .device
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: None,
-                contents,
-                usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+        self.create_buffer(BufferDescriptor {
+            label: "index buffer",
+            contents: Some(contents),
+            usage: buffer_usage::INDEX | buffer_usage::COPY_SRC | buffer_usage::COPY_TARGET,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn create_transient_buffer(
+        &mut self,
+        contents: &[u8],
+        usage: buffer_usage::BufferUsage,
+    ) -> Result<BufferHandle, BufferError> {
+        // queue writes have to be 4 byte aligned
+        let size = wgpu::util::align_to(contents.len(), wgpu::COPY_BUFFER_ALIGNMENT as usize);
+
+        let free = self
+            .transient_buffers
+            .iter_mut()
+            .find(|e| !e.in_use && e.usage == usage && e.size >= size);
+
+        let handle = if let Some(transient) = free {
+            transient.in_use = true;
+            transient.handle
+        } else {
+            // sizes are rounded up so buffers can be reused by similarly sized uploads
+            let size = size.max(256).next_power_of_two();
+            let handle = self.create_buffer(BufferDescriptor {
+                label: "transient buffer",
+                size,
+                usage: usage | buffer_usage::COPY_TARGET,
+                ..Default::default()
+            })?;
+            self.transient_buffers.push(TransientBuffer {
+                handle,
+                usage,
+                size,
+                in_use: true,
             });
+            handle
+        };
 
-        self.buffers.push(buffer);
-        BufferHandle::uniform(self.buffers.len() - 1)
+        if size == contents.len() {
+            self.write_buffer(handle, contents);
+        } else {
+            let mut padded = contents.to_vec();
+            padded.resize(size, 0);
+            self.write_buffer(handle, &padded);
+        }
+
+        Ok(handle)
     }
 
     fn write_buffer(&mut self, buffer: BufferHandle, data: &[u8]) {
+        self.write_buffer_at(buffer, 0, data);
+    }
+
+    fn write_buffer_at(&mut self, buffer: BufferHandle, offset: u64, data: &[u8]) {
         self.queue
-            .write_buffer(&self.buffers[buffer.index], 0, data);
+            .write_buffer(&self.buffers[buffer.index], offset, data);
     }
 
-    fn new_compute_pass(&mut self) -> WGPUComputePass {
+    fn copy_buffer_to_buffer(
+        &mut self,
+        source: BufferHandle,
+        source_offset: u64,
+        destination: BufferHandle,
+        destination_offset: u64,
+        size: u64,
+    ) {
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Copy Encoder"),
+            });
+
+        encoder.copy_buffer_to_buffer(
+            &self.buffers[source.index],
+            source_offset,
+            &self.buffers[destination.index],
+            destination_offset,
+            size,
+        );
+
+        self.queue.submit(Some(encoder.finish()));
+    }
+
+    fn resize_buffer(&mut self, buffer: BufferHandle, size: usize) {
+        let old_buffer = &self.buffers[buffer.index];
+        let usage = old_buffer.usage();
+
+        let new_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(self.buffer_labels[buffer.index]),
+            size: size as u64,
+            usage,
+            mapped_at_creation: false,
+        });
+
+        // copies have to be 4 byte aligned, anything past that is lost
+        let copy_size = old_buffer.size().min(size as u64) & !(wgpu::COPY_BUFFER_ALIGNMENT - 1);
+        if usage.contains(wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST) {
+            if copy_size > 0 {
+                let mut encoder =
+                    self.device
+                        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                            label: Some("Resize Encoder"),
+                        });
+                encoder.copy_buffer_to_buffer(old_buffer, 0, &new_buffer, 0, copy_size);
+                self.queue.submit(Some(encoder.finish()));
+            }
+        } else {
+            log::warn!(
+                "resizing buffer {} without COPY_SRC and COPY_TARGET usage, contents are lost",
+                buffer.index
+            );
+        }
+
+        self.buffers[buffer.index] = new_buffer;
+        self.bind_group_dependencies
+            .resource_changed(BoundResource::Buffer(buffer.index));
+    }
+
+    fn replace_buffer(&mut self, buffer: BufferHandle, contents: &[u8]) {
+        let usage = self.buffers[buffer.index].usage();
+
+        self.buffers[buffer.index] =
+            self.device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(self.buffer_labels[buffer.index]),
+                    contents,
+                    usage,
+                });
+        self.bind_group_dependencies
+            .resource_changed(BoundResource::Buffer(buffer.index));
+    }
+
+    fn new_compute_pass(&mut self) -> WGPUComputePass<'_> {
         WGPUComputePass::new(self)
     }
 
     fn submit_pass(&mut self, pass: RenderPass) {
+        self.recover_lost_device();
+
         let RenderPass { window, steps } = pass;
 
         let mut encoder = self
@@ -273,34 +654,81 @@ impl Janderer for WGPURenderer {
             });
 
         let surface_texture_view = {
-            let surface = self.surfaces.get_mut(&window.id()).unwrap();
-
-            let surface_texture = if let Some(surface_texture) = &surface.surface_texture {
-                surface_texture
-            } else {
-                let surface_texture = match surface.surface.get_current_texture() {
-                    Ok(surface) => surface,
-                    Err(e) => {
-                        panic!("{e}");
-                    }
-                };
-                surface.surface_texture = Some(surface_texture);
-                surface.surface_texture.as_ref().unwrap()
+            // the window could have closed earlier in the frame
+            let Some(surface) = self.surfaces.get_mut(&window.id()) else {
+                assert!(
+                    window.should_close(),
+                    "submitting a pass for window {} that was never registered",
+                    window.id()
+                );
+                return;
+            };
+            let Some(surface_texture) = surface.current_texture(&self.device, window) else {
+                return;
             };
-
             surface_texture
                 .texture
                 .create_view(&wgpu::TextureViewDescriptor::default())
         };
+        // the surface could've been resized when acquiring
+        self.resize_screen_textures();
+
+        // before anything is created or rebuilt for the pass, so that can't panic first
+        if self.validate_passes {
+            if let Err(err) = self.validate_pass(window, &steps) {
+                panic!("invalid render pass for window {}, {err}", window.id());
+            }
+        }
+
+        let screen_depth = steps
+            .iter()
+            .any(|e| e.depth_tex == Some(TargetTexture::Screen));
+        self.surfaces
+            .get_mut(&window.id())
+            .unwrap()
+            .prepare_targets(&self.device, screen_depth);
+        self.rebuild_dirty_bind_groups();
+
+        // pipelines are compiled for the sample count of what they draw into
+        let msaa_samples = self.surfaces[&window.id()].msaa_samples;
+        let steps_samples = steps
+            .iter()
+            .map(|e| self.step_samples(e, msaa_samples))
+            .collect::<Vec<_>>();
+        for (step, samples) in steps.iter().zip(steps_samples.iter()) {
+            if let Some(shader) = step.shader {
+                self.create_missing_pipeline(shader, step.variant, *samples)
+                    .unwrap_or_else(|err| {
+                        panic!(
+                            "shader \"{}\": {err}",
+                            self.shader_descriptors[shader.0].name
+                        )
+                    });
+            }
+        }
+
+        if cfg!(debug_assertions) {
+            for step in steps.iter() {
+                if let Some(shader) = step.shader {
+                    self.check_shader_data_layouts(shader, step.variant, &step.bind_groups);
+                }
+            }
+        }
+        let surface = &self.surfaces[&window.id()];
 
         let mut render_pass = None;
         let previous_target = TargetTexture::Screen;
 
+        // what the current render pass has bound, for counting switches
+        let mut bound_pipeline = None;
+        let mut bound_bind_groups = HashMap::new();
+
         let len = steps.len() - 1;
         for (i, step) in steps.iter().enumerate().take(len) {
             let RenderStep {
                 action,
                 shader,
+                variant,
                 bind_groups,
                 target,
                 depth_tex,
@@ -324,7 +752,7 @@ impl Janderer for WGPURenderer {
                 let color_attachment = match target {
                     TargetTexture::Screen | TargetTexture::Handle(..) => {
                         let (view, resolve_target) = match target {
-                            TargetTexture::Screen => (&surface_texture_view, None),
+                            TargetTexture::Screen => surface.color_views(&surface_texture_view),
                             TargetTexture::Handle(texture_handle) => {
                                 let resolve_target = match resolve_target {
                                     Some(target) => match target {
@@ -364,9 +792,16 @@ impl Janderer for WGPURenderer {
                     TargetTexture::None => None,
                 };
 
+                let depth_view = match depth_tex {
+                    Some(TargetTexture::Screen) => Some(surface.depth_view()),
+                    Some(TargetTexture::Handle(texture_handle)) => {
+                        Some(&self.textures[texture_handle.0].view)
+                    }
+                    Some(TargetTexture::None) | None => None,
+                };
                 let depth_stencil_attachment =
-                    depth_tex.map(|tex| wgpu::RenderPassDepthStencilAttachment {
-                        view: &self.textures[tex.0].view,
+                    depth_view.map(|view| wgpu::RenderPassDepthStencilAttachment {
+                        view,
                         depth_ops: Some(wgpu::Operations {
                             load: if let Some(depth) = depth {
                                 wgpu::LoadOp::Clear(*depth)
@@ -387,16 +822,25 @@ impl Janderer for WGPURenderer {
                 });
 
                 render_pass = Some(new_pass);
+                bound_pipeline = None;
+                bound_bind_groups.clear();
             }
 
             let render_pass = render_pass.as_mut().unwrap();
 
             if let Some(shader) = shader {
+                if bound_pipeline != Some((*shader, *variant)) {
+                    bound_pipeline = Some((*shader, *variant));
+                    self.frame_stats.pipeline_switches += 1;
+                }
                 let shader = self.shaders.get(shader.0).unwrap();
-                render_pass.set_pipeline(&shader.pipeline);
+                render_pass.set_pipeline(&shader.variants[&(*variant, steps_samples[i])]);
             }
 
             for (index, handle) in bind_groups.iter() {
+                if bound_bind_groups.insert(*index, *handle) != Some(*handle) {
+                    self.frame_stats.bind_group_switches += 1;
+                }
                 render_pass.set_bind_group(
                     *index,
                     &self.bind_groups_render_data[handle.0].bind_group,
@@ -406,19 +850,14 @@ impl Janderer for WGPURenderer {
 
             match action {
                 crate::render_pass::RenderAction::Mesh {
-                    vertex_buffer_handle,
+                    vertex_buffer_handles,
                     index_buffer_handle,
-                    instance_buffer_handle,
                     range,
                     num_indices,
                 } => {
-                    render_pass
-                        .set_vertex_buffer(0, self.buffers[vertex_buffer_handle.index].slice(..));
-                    if let Some(instance_buffer_handle) = instance_buffer_handle {
-                        render_pass.set_vertex_buffer(
-                            1,
-                            self.buffers[instance_buffer_handle.index].slice(..),
-                        );
+                    for (slot, handle) in vertex_buffer_handles.iter().enumerate() {
+                        render_pass
+                            .set_vertex_buffer(slot as u32, self.buffers[handle.index].slice(..));
                     }
                     render_pass.set_index_buffer(
                         self.buffers[index_buffer_handle.index].slice(..),
@@ -426,6 +865,19 @@ impl Janderer for WGPURenderer {
                     );
 
                     render_pass.draw_indexed(0..*num_indices, 0, range.clone());
+
+                    let instances = range.len() as u64;
+                    let stripped = bound_pipeline.map_or(false, |(shader, _)| {
+                        self.shader_descriptors[shader.0].stripped
+                    });
+                    let triangles = if stripped {
+                        num_indices.saturating_sub(2)
+                    } else {
+                        num_indices / 3
+                    };
+                    self.frame_stats.draw_calls += 1;
+                    self.frame_stats.instances += instances;
+                    self.frame_stats.triangles += triangles as u64 * instances;
                 }
                 crate::render_pass::RenderAction::Empty => {}
             }
@@ -434,164 +886,147 @@ impl Janderer for WGPURenderer {
         if render_pass.is_some() {
             drop(render_pass);
             self.queue.submit(std::iter::once(encoder.finish()));
+            self.frame_stats.passes += 1;
         }
     }
 
-    fn create_shader_at(&mut self, desc: ShaderDescriptor, handle: ShaderHandle) {
-        //ugly ass code fuck off
-        let bind_group_layouts =
-            Self::get_layout_descriptors(&self.device, &desc.bind_group_layout_descriptors);
-        let bind_group_ref = bind_group_layouts.iter().collect::<Vec<_>>();
-
-        let layout = self
-            .device
-            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: Some("Shader Layout"),
-                bind_group_layouts: &bind_group_ref,
-                push_constant_ranges: &[],
-            });
-
-        let code = match &desc.source {
-            crate::shader::ShaderSource::Code(source) => source.clone(),
-            #[cfg(not(target_arch = "wasm32"))]
-            crate::shader::ShaderSource::File(file_path) => {
-                pollster::block_on(load_text(file_path.clone())).unwrap()
-            }
-        };
-
-        let shader = wgpu::ShaderModuleDescriptor {
-            label: Some("Shader"),
-            source: wgpu::ShaderSource::Wgsl(code.clone().into()),
-        };
-        let shader = self.device.create_shader_module(shader);
-
-        let target = match &desc.target_texture_format {
-            Some(format) => {
-                let format = match format {
-                    #[cfg(target_arch = "wasm32")]
-                    TextureFormat::Rgba8U => wgpu::TextureFormat::Rgba8Unorm,
-                    #[cfg(target_arch = "wasm32")]
-                    TextureFormat::Bgra8U => wgpu::TextureFormat::Bgra8Unorm,
-                    #[cfg(not(target_arch = "wasm32"))]
-                    TextureFormat::Rgba8U => wgpu::TextureFormat::Rgba8UnormSrgb,
-                    #[cfg(not(target_arch = "wasm32"))]
-                    TextureFormat::Bgra8U => wgpu::TextureFormat::Bgra8UnormSrgb,
-                    TextureFormat::F32 => wgpu::TextureFormat::R32Float,
-                    TextureFormat::Depth32F => wgpu::TextureFormat::Depth32Float,
-                    TextureFormat::Depth16U => wgpu::TextureFormat::Depth16Unorm,
-                };
+    fn create_shader_at(
+        &mut self,
+        desc: ShaderDescriptor,
+        handle: ShaderHandle,
+    ) -> Result<(), ShaderCompileError> {
+        if desc.features.len() > 64 {
+            return Err(ShaderCompileError::TooManyFeatures(desc.features.len()));
+        }
 
-                let blend = if format == wgpu::TextureFormat::R32Float {
-                    None
-                } else {
-                    Some(wgpu::BlendState::ALPHA_BLENDING)
+        // variants that were already compiled get rebuilt from the new descriptor, matched
+        // by feature name since its features can be in another order. ones using features
+        // it doesn't have anymore are dropped
+        let mut pipelines = self
+            .screen_sample_counts(&desc)
+            .into_iter()
+            .map(|samples| (ShaderVariant::default(), samples))
+            .collect::<Vec<_>>();
+        if let Some(shader) = self.shaders.get(handle.0) {
+            let old_desc = &self.shader_descriptors[handle.0];
+            for (variant, samples) in shader.variants.keys() {
+                let Some(variant) = desc.variant(&old_desc.variant_features(*variant)) else {
+                    continue;
                 };
-
-                Some(wgpu::ColorTargetState {
-                    format,
-                    blend,
-                    write_mask: wgpu::ColorWrites::ALL,
-                })
+                if !pipelines.contains(&(variant, *samples)) {
+                    pipelines.push((variant, *samples));
+                }
             }
-            None => None,
-        };
-
-        let attributes = desc
-            .descriptors
-            .iter()
-            .map(Self::get_buffer_attributes)
-            .collect::<Vec<_>>();
-        let buffers = Self::get_buffer_layouts(&attributes, &desc.descriptors);
-
-        let pipeline = self
-            .device
-            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some(&desc.name),
-                layout: Some(&layout),
-                vertex: wgpu::VertexState {
-                    module: &shader,
-                    entry_point: desc.vs_entry,
-                    buffers: &buffers,
-                    compilation_options: Default::default(),
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module: &shader,
-                    entry_point: desc.fs_entry,
-                    targets: &[target],
-                    compilation_options: Default::default(),
-                }),
-                primitive: wgpu::PrimitiveState {
-                    topology: if desc.stripped {
-                        wgpu::PrimitiveTopology::TriangleStrip
-                    } else {
-                        wgpu::PrimitiveTopology::TriangleList
-                    },
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Ccw,
-                    cull_mode: if desc.backface_culling {
-                        Some(wgpu::Face::Back)
-                    } else {
-                        None
-                    },
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    unclipped_depth: false,
-                    conservative: false,
-                },
-                depth_stencil: if desc.depth {
-                    Some(wgpu::DepthStencilState {
-                        format: wgpu::TextureFormat::Depth32Float,
-                        depth_write_enabled: true,
-                        depth_compare: wgpu::CompareFunction::Less,
-                        stencil: wgpu::StencilState::default(),
-                        bias: wgpu::DepthBiasState::default(),
-                    })
-                } else {
-                    None
-                },
-                multisample: wgpu::MultisampleState {
-                    count: desc.multisample,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
-                multiview: None,
-                cache: None,
-            });
+        }
 
-        let shader = WGPUShader { pipeline };
+        let mut shader = WGPUShader::default();
+        for (variant, samples) in pipelines {
+            self.create_shader_pipeline(&mut shader, &desc, variant, samples)?;
+        }
+        self.checked_data_layouts.clear();
 
         if handle.0 >= self.shaders.len() {
             self.shaders.push(shader);
             self.shader_descriptors.push(desc);
         } else {
             self.shaders[handle.0] = shader;
+            self.shader_descriptors[handle.0] = desc;
         }
+        Ok(())
     }
 
-    fn create_shader(&mut self, desc: ShaderDescriptor) -> ShaderHandle {
-        self.create_shader_at(desc, ShaderHandle(self.shaders.len()));
-        ShaderHandle(self.shaders.len() - 1)
+    fn create_shader(
+        &mut self,
+        desc: ShaderDescriptor,
+    ) -> Result<ShaderHandle, ShaderCompileError> {
+        self.create_shader_at(desc, ShaderHandle(self.shaders.len()))?;
+        Ok(ShaderHandle(self.shaders.len() - 1))
     }
 
-    fn reload_shader(&mut self, handle: ShaderHandle) {
-        self.create_shader_at(self.shader_descriptors[handle.0].clone(), handle);
+    fn reload_shader(&mut self, handle: ShaderHandle) -> Result<(), ShaderCompileError> {
+        self.create_shader_at(self.shader_descriptors[handle.0].clone(), handle)
     }
 
-    fn reload_shaders(&mut self) {
+    fn reload_shaders(&mut self) -> Result<(), ShaderCompileError> {
         for i in 0..self.shaders.len() {
-            self.reload_shader(ShaderHandle(i));
+            self.reload_shader(ShaderHandle(i))?;
         }
+        Ok(())
+    }
+
+    fn set_device_lost_callback(&mut self, callback: impl FnMut(&mut Self) + 'static) {
+        self.device_lost_callback = Some(Box::new(callback));
+    }
+
+    fn stats(&self) -> RendererStats {
+        RendererStats {
+            frame: self.last_frame_stats,
+            memory: self.memory_stats(),
+        }
+    }
+
+    fn save_pipeline_cache(&self) {
+        if let Some(pipeline_cache) = &self.pipeline_cache {
+            pipeline_cache.save();
+        }
+    }
+
+    fn get_shader_variant(
+        &mut self,
+        shader: ShaderHandle,
+        features: &[&str],
+    ) -> Result<ShaderVariant, ShaderCompileError> {
+        let desc = &self.shader_descriptors[shader.0];
+        let Some(variant) = desc.variant(features) else {
+            return Err(ShaderCompileError::UnknownFeatures {
+                features: desc.features.iter().map(|e| e.to_string()).collect(),
+                requested: features.iter().map(|e| e.to_string()).collect(),
+            });
+        };
+
+        for samples in self.screen_sample_counts(&self.shader_descriptors[shader.0]) {
+            self.create_missing_pipeline(shader, variant, samples)?;
+        }
+
+        Ok(variant)
     }
 
     fn create_texture_at(&mut self, desc: TextureDescriptor, handle: TextureHandle) {
+        let texture_size = match desc.size {
+            TextureSize::Absolute(size) => {
+                self.screen_textures.remove(&handle.0);
+                size
+            }
+            TextureSize::Screen(window, scale) => {
+                assert!(
+                    desc.data.is_none(),
+                    "texture \"{}\" is sized to a window, it can't be created with data",
+                    desc.name
+                );
+                self.screen_textures.insert(
+                    handle.0,
+                    TextureDescriptor {
+                        name: desc.name.clone(),
+                        size: desc.size,
+                        sample_count: desc.sample_count,
+                        data: None,
+                        format: desc.format,
+                        usage: desc.usage,
+                    },
+                );
+                self.screen_texture_size(window, scale)
+            }
+        };
         let size = wgpu::Extent3d {
-            width: desc.size.x,
-            height: desc.size.y,
+            width: texture_size.x,
+            height: texture_size.y,
             depth_or_array_layers: 1,
         };
 
         let (format, channels) = match desc.format {
             TextureFormat::Rgba8U => (wgpu::TextureFormat::Rgba8UnormSrgb, 4),
             TextureFormat::Bgra8U => (wgpu::TextureFormat::Bgra8UnormSrgb, 4),
+            TextureFormat::Rgba16F => (wgpu::TextureFormat::Rgba16Float, 8),
             TextureFormat::F32 => (wgpu::TextureFormat::R32Float, 4),
             TextureFormat::Depth32F => (wgpu::TextureFormat::Depth32Float, 1),
             TextureFormat::Depth16U => (wgpu::TextureFormat::Depth16Unorm, 1),
@@ -612,7 +1047,7 @@ impl Janderer for WGPURenderer {
         }
 
         let texture = self.device.create_texture(&wgpu::TextureDescriptor {
-            label: Some(desc.name),
+            label: Some(&desc.name),
             size,
             mip_level_count: 1,
             sample_count: desc.sample_count,
@@ -645,13 +1080,17 @@ impl Janderer for WGPURenderer {
         let texture = Texture {
             texture,
             view,
-            width: desc.size.x,
-            height: desc.size.y,
+            width: texture_size.x,
+            height: texture_size.y,
         };
         if handle.0 >= self.textures.len() {
             self.textures.push(texture);
+            self.texture_labels.push(desc.name);
         } else {
             self.textures[handle.0] = texture;
+            self.texture_labels[handle.0] = desc.name;
+            self.bind_group_dependencies
+                .resource_changed(BoundResource::Texture(handle.0));
         }
     }
 
@@ -666,6 +1105,7 @@ impl Janderer for WGPURenderer {
 
     fn add_texture(&mut self, texture: Texture) -> TextureHandle {
         self.textures.push(texture);
+        self.texture_labels.push("texture".into());
         TextureHandle(self.textures.len() - 1)
     }
 
@@ -693,7 +1133,7 @@ impl Janderer for WGPURenderer {
             SamplerCompareFunction::Greater => wgpu::CompareFunction::Greater,
         });
 
-        let sampler = self.device.create_sampler(&wgpu::SamplerDescriptor {
+        let descriptor = wgpu::SamplerDescriptor {
             address_mode_u: address_mode,
             address_mode_v: address_mode,
             address_mode_w: address_mode,
@@ -704,9 +1144,10 @@ impl Janderer for WGPURenderer {
             lod_max_clamp,
             compare,
             ..Default::default()
-        });
+        };
 
-        self.samplers.push(sampler);
+        self.samplers.push(self.device.create_sampler(&descriptor));
+        self.sampler_descriptors.push(descriptor);
         SamplerHandle(self.samplers.len() - 1)
     }
 
@@ -720,6 +1161,8 @@ impl Janderer for WGPURenderer {
 
     fn get_bind_group_mut(&mut self, handle: UntypedBindGroupHandle) -> Option<&mut dyn BindGroup> {
         if let Some(b) = self.bind_groups.get_mut(handle.0) {
+            // its handles could be changed
+            self.bind_group_dependencies.touch(handle.0);
             Some(b.as_mut())
         } else {
             None
@@ -731,30 +1174,17 @@ impl Janderer for WGPURenderer {
         bind_group: Box<dyn BindGroup>,
         handle: UntypedBindGroupHandle,
     ) {
-        {
-            let layout = bind_group.get_layout();
-            let (bind_group_layout, entries) = self.get_layout_and_entries(&self.device, &layout);
-
-            let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
-                layout: &bind_group_layout,
-                entries: &entries[..],
-                label: Some("bind_group"),
-            });
-
-            let data = WGPUBindGroupRenderData { bind_group };
-
-            if handle.0 >= self.bind_groups.len() {
-                self.bind_groups_render_data.push(data);
-            } else {
-                self.bind_groups_render_data[handle.0] = data;
-            }
-        }
-
         if handle.0 >= self.bind_groups.len() {
             self.bind_groups.push(bind_group);
         } else {
             self.bind_groups[handle.0] = bind_group;
         }
+        self.rebuild_bind_group(handle.0);
+        self.checked_data_layouts.clear();
+    }
+
+    fn update_bind_group(&mut self, handle: UntypedBindGroupHandle) {
+        self.rebuild_bind_group(handle.0);
     }
 
     fn create_bind_group(&mut self, bind_group: Box<dyn BindGroup>) -> UntypedBindGroupHandle {
@@ -804,53 +1234,55 @@ impl Janderer for WGPURenderer {
             if let Some(e) = surface.surface_texture.take() {
                 e.present()
             }
+            surface.skip_frame = false;
         });
+
+        self.last_frame_stats = std::mem::take(&mut self.frame_stats);
+
+        // runs wgpu's callbacks, including the device lost one
+        self.device.poll(wgpu::Maintain::Poll);
+        self.recover_lost_device();
+
+        self.transient_buffers
+            .iter_mut()
+            .for_each(|e| e.in_use = false);
     }
 
     fn create_compute_shader_at(
         &mut self,
         desc: crate::shader::ComputeShaderDescriptor,
         handle: ComputeShaderHandle,
-    ) {
-        let code = match &desc.source {
-            crate::shader::ShaderSource::Code(source) => source.clone(),
-            #[cfg(not(target_arch = "wasm32"))]
-            crate::shader::ShaderSource::File(file_path) => {
-                pollster::block_on(load_text(file_path.clone())).unwrap()
-            }
-        };
+    ) -> Result<(), ShaderCompileError> {
+        let compiled = desc.source.compile(
+            &desc.entry,
+            &[(naga::ShaderStage::Compute, &desc.entry)],
+            &desc.defines,
+        )?;
 
-        let shader_desc = wgpu::ShaderModuleDescriptor {
-            label: Some("Shader"),
-            source: wgpu::ShaderSource::Wgsl(code.into()),
-        };
-        let shader = self.device.create_shader_module(shader_desc);
+        let mut resolved = desc.clone();
+        let reflection = resolved.resolve_layouts(&compiled, self.validate_passes)?;
 
-        //ugly ass code fuck off
-        let bind_group_layouts =
-            Self::get_layout_descriptors(&self.device, &desc.bind_group_layout_descriptors);
-        let bind_group_ref = bind_group_layouts.iter().collect::<Vec<_>>();
+        let shader = self.create_shader_modules(compiled).remove(0);
 
-        let layout = self
-            .device
-            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: None,
-                bind_group_layouts: &bind_group_ref,
-                push_constant_ranges: &[],
-            });
+        self.cache_pipeline_layout(&resolved.bind_group_layout_descriptors);
+        let layout = &self.pipeline_layouts[&resolved.bind_group_layout_descriptors];
 
         let pipeline = self
             .device
             .create_compute_pipeline(&ComputePipelineDescriptor {
                 label: Some("Compute Pipeline"),
-                layout: Some(&layout),
+                layout: Some(layout),
                 module: &shader,
-                entry_point: desc.entry,
+                entry_point: &desc.entry,
                 compilation_options: Default::default(),
-                cache: None,
+                cache: self.pipeline_cache(),
             });
 
-        let shader = WGPUComputeShader { pipeline };
+        let shader = WGPUComputeShader {
+            pipeline,
+            reflection,
+        };
+        self.checked_data_layouts.clear();
 
         if handle.0 >= self.compute_shaders.len() {
             self.compute_shaders.push(shader);
@@ -858,24 +1290,253 @@ impl Janderer for WGPURenderer {
         } else {
             self.compute_shaders[handle.0] = shader;
         }
+        Ok(())
     }
 
     fn create_compute_shader(
         &mut self,
         desc: crate::shader::ComputeShaderDescriptor,
-    ) -> ComputeShaderHandle {
-        self.create_compute_shader_at(desc, ComputeShaderHandle(self.compute_shaders.len()));
-        ComputeShaderHandle(self.compute_shaders.len() - 1)
+    ) -> Result<ComputeShaderHandle, ShaderCompileError> {
+        self.create_compute_shader_at(desc, ComputeShaderHandle(self.compute_shaders.len()))?;
+        Ok(ComputeShaderHandle(self.compute_shaders.len() - 1))
     }
 
-    fn re_create_compute_shader(&mut self, handle: ComputeShaderHandle) {
+    fn re_create_compute_shader(
+        &mut self,
+        handle: ComputeShaderHandle,
+    ) -> Result<(), ShaderCompileError> {
         let descriptor = &self.compute_shader_descriptors[handle.0];
-        self.create_compute_shader_at(descriptor.clone(), handle);
+        self.create_compute_shader_at(descriptor.clone(), handle)
     }
 
-    fn re_create_compute_shaders(&mut self) {
+    fn re_create_compute_shaders(&mut self) -> Result<(), ShaderCompileError> {
         for i in 0..self.compute_shaders.len() {
-            self.re_create_compute_shader(ComputeShaderHandle(i));
+            self.re_create_compute_shader(ComputeShaderHandle(i))?;
+        }
+        Ok(())
+    }
+}
+
+impl WGPURenderer {
+    // the format a render target of this type has, the surface uses the same ones
+    fn target_format(format: TextureFormat) -> wgpu::TextureFormat {
+        match format {
+            #[cfg(target_arch = "wasm32")]
+            TextureFormat::Rgba8U => wgpu::TextureFormat::Rgba8Unorm,
+            #[cfg(target_arch = "wasm32")]
+            TextureFormat::Bgra8U => wgpu::TextureFormat::Bgra8Unorm,
+            #[cfg(not(target_arch = "wasm32"))]
+            TextureFormat::Rgba8U => wgpu::TextureFormat::Rgba8UnormSrgb,
+            #[cfg(not(target_arch = "wasm32"))]
+            TextureFormat::Bgra8U => wgpu::TextureFormat::Bgra8UnormSrgb,
+            TextureFormat::Rgba16F => wgpu::TextureFormat::Rgba16Float,
+            TextureFormat::F32 => wgpu::TextureFormat::R32Float,
+            TextureFormat::Depth32F => wgpu::TextureFormat::Depth32Float,
+            TextureFormat::Depth16U => wgpu::TextureFormat::Depth16Unorm,
+        }
+    }
+
+    // one module for wgsl and spir-v, one per stage for glsl
+    fn create_shader_modules(&self, shader: CompiledShader) -> Vec<wgpu::ShaderModule> {
+        let sources = match shader {
+            CompiledShader::Wgsl { code, .. } => vec![wgpu::ShaderSource::Wgsl(code.into())],
+            CompiledShader::SpirV(module) => vec![wgpu::ShaderSource::Naga(Cow::Owned(module))],
+            CompiledShader::Glsl { vertex, fragment } => vec![
+                wgpu::ShaderSource::Naga(Cow::Owned(*vertex)),
+                wgpu::ShaderSource::Naga(Cow::Owned(*fragment)),
+            ],
+        };
+
+        sources
+            .into_iter()
+            .map(|source| {
+                self.device
+                    .create_shader_module(wgpu::ShaderModuleDescriptor {
+                        label: Some("Shader"),
+                        source,
+                    })
+            })
+            .collect()
+    }
+
+    fn create_missing_pipeline(
+        &mut self,
+        shader: ShaderHandle,
+        variant: ShaderVariant,
+        samples: u32,
+    ) -> Result<(), ShaderCompileError> {
+        if self.shaders[shader.0]
+            .variants
+            .contains_key(&(variant, samples))
+        {
+            return Ok(());
+        }
+
+        let desc = self.shader_descriptors[shader.0].clone();
+        let mut wgpu_shader = std::mem::take(&mut self.shaders[shader.0]);
+        let result = self.create_shader_pipeline(&mut wgpu_shader, &desc, variant, samples);
+        self.shaders[shader.0] = wgpu_shader;
+        result
+    }
+
+    // what the shader draws to windows with, compiled up front so frames don't stall on them.
+    // passes drawing into other multisampled textures compile theirs when submitted
+    fn screen_sample_counts(&self, desc: &ShaderDescriptor) -> Vec<u32> {
+        let format = desc.target_texture_format.map(Self::target_format);
+        let mut counts = vec![1];
+        for surface in self.surfaces.values() {
+            if Some(surface.config.format) == format && !counts.contains(&surface.msaa_samples) {
+                counts.push(surface.msaa_samples);
+            }
+        }
+        counts
+    }
+
+    // a step draws with the samples of its target, or of its depth when it has no color
+    fn step_samples(&self, step: &RenderStep, msaa_samples: u32) -> u32 {
+        let samples = |target: &TargetTexture| match target {
+            TargetTexture::Screen => Some(msaa_samples),
+            TargetTexture::Handle(handle) => Some(self.textures[handle.0].texture.sample_count()),
+            TargetTexture::None => None,
+        };
+        samples(&step.target)
+            .or_else(|| step.depth_tex.as_ref().and_then(samples))
+            .unwrap_or(1)
+    }
+
+    fn compile_shader_variant(
+        &mut self,
+        desc: &ShaderDescriptor,
+        variant: ShaderVariant,
+    ) -> Result<CompiledVariant, ShaderCompileError> {
+        let compiled = desc.source.compile(
+            &desc.name,
+            &[
+                (naga::ShaderStage::Vertex, &desc.vs_entry),
+                (naga::ShaderStage::Fragment, &desc.fs_entry),
+            ],
+            &desc.variant_defines(variant),
+        )?;
+
+        // reflection fills in a copy so reloads and other variants reflect their own source
+        let mut resolved = desc.clone();
+        let reflection = resolved.resolve_layouts(&compiled, self.validate_passes)?;
+
+        let vs_entry = compiled.entry_point(&desc.vs_entry).to_string();
+        let fs_entry = compiled.entry_point(&desc.fs_entry).to_string();
+
+        self.cache_pipeline_layout(&resolved.bind_group_layout_descriptors);
+
+        Ok(CompiledVariant {
+            modules: self.create_shader_modules(compiled),
+            vs_entry,
+            fs_entry,
+            reflection,
+            resolved,
+        })
+    }
+
+    // compiles the variant into `shader` the first time one of its sample counts is asked for
+    fn create_shader_pipeline(
+        &mut self,
+        shader: &mut WGPUShader,
+        desc: &ShaderDescriptor,
+        variant: ShaderVariant,
+        samples: u32,
+    ) -> Result<(), ShaderCompileError> {
+        if let Entry::Vacant(entry) = shader.compiled.entry(variant) {
+            entry.insert(self.compile_shader_variant(desc, variant)?);
         }
+        let CompiledVariant {
+            modules,
+            vs_entry,
+            fs_entry,
+            resolved,
+            ..
+        } = &shader.compiled[&variant];
+        let layout = &self.pipeline_layouts[&resolved.bind_group_layout_descriptors];
+
+        let target = match &desc.target_texture_format {
+            Some(format) => {
+                let format = Self::target_format(*format);
+
+                let blend = if format == wgpu::TextureFormat::R32Float {
+                    None
+                } else {
+                    Some(wgpu::BlendState::ALPHA_BLENDING)
+                };
+
+                Some(wgpu::ColorTargetState {
+                    format,
+                    blend,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })
+            }
+            None => None,
+        };
+
+        let attributes = resolved
+            .descriptors
+            .iter()
+            .map(Self::get_buffer_attributes)
+            .collect::<Vec<_>>();
+        let buffers = Self::get_buffer_layouts(&attributes, &resolved.descriptors);
+
+        let pipeline = self
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(&desc.name),
+                layout: Some(layout),
+                vertex: wgpu::VertexState {
+                    module: &modules[0],
+                    entry_point: vs_entry,
+                    buffers: &buffers,
+                    compilation_options: Default::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: modules.last().unwrap(),
+                    entry_point: fs_entry,
+                    targets: &[target],
+                    compilation_options: Default::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: if desc.stripped {
+                        wgpu::PrimitiveTopology::TriangleStrip
+                    } else {
+                        wgpu::PrimitiveTopology::TriangleList
+                    },
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: if desc.backface_culling {
+                        Some(wgpu::Face::Back)
+                    } else {
+                        None
+                    },
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                depth_stencil: if desc.depth {
+                    Some(wgpu::DepthStencilState {
+                        format: wgpu::TextureFormat::Depth32Float,
+                        depth_write_enabled: true,
+                        depth_compare: wgpu::CompareFunction::Less,
+                        stencil: wgpu::StencilState::default(),
+                        bias: wgpu::DepthBiasState::default(),
+                    })
+                } else {
+                    None
+                },
+                multisample: wgpu::MultisampleState {
+                    count: samples,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+                cache: self.pipeline_cache(),
+            });
+
+        shader.variants.insert((variant, samples), pipeline);
+        Ok(())
     }
 }
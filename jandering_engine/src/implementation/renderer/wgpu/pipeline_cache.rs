@@ -0,0 +1,63 @@
+use std::path::{Path, PathBuf};
+
+use super::WGPURenderer;
+
+pub(super) struct PipelineCache {
+    pub cache: wgpu::PipelineCache,
+    path: PathBuf,
+}
+
+impl PipelineCache {
+    // None when the backend can't cache pipelines, only vulkan drivers can for now
+    pub fn new(device: &wgpu::Device, adapter: &wgpu::Adapter, dir: &Path) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::PIPELINE_CACHE) {
+            return None;
+        }
+
+        // the key names the driver, so a cache from a different gpu is never loaded
+        let key = wgpu::util::pipeline_cache_key(&adapter.get_info())?;
+        let path = dir.join(key);
+        let data = std::fs::read(&path).ok();
+
+        // safe as long as the file was written by save() below, wgpu falls back to an empty
+        // cache if the data is from an older driver or otherwise invalid
+        let cache = unsafe {
+            device.create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+                label: Some("Pipeline Cache"),
+                data: data.as_deref(),
+                fallback: true,
+            })
+        };
+
+        Some(Self { cache, path })
+    }
+
+    // after the device was lost, whatever was saved last is loaded again
+    pub fn recreate(self, device: &wgpu::Device, adapter: &wgpu::Adapter) -> Option<Self> {
+        Self::new(device, adapter, self.path.parent()?)
+    }
+
+    pub fn save(&self) {
+        let Some(data) = self.cache.get_data() else {
+            return;
+        };
+
+        // written next to the old file first so a crash mid write doesn't leave a broken cache
+        let temp = self.path.with_extension("tmp");
+        let result = self
+            .path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&temp, data))
+            .and_then(|_| std::fs::rename(&temp, &self.path));
+        if let Err(err) = result {
+            log::warn!("couldn't save pipeline cache to {:?}: {}", self.path, err);
+        }
+    }
+}
+
+impl WGPURenderer {
+    pub(super) fn pipeline_cache(&self) -> Option<&wgpu::PipelineCache> {
+        self.pipeline_cache.as_ref().map(|e| &e.cache)
+    }
+}
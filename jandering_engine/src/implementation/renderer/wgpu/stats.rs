@@ -0,0 +1,50 @@
+use crate::stats::MemoryStats;
+
+use super::WGPURenderer;
+
+impl WGPURenderer {
+    pub(super) fn memory_stats(&self) -> MemoryStats {
+        let mut stats = MemoryStats::default();
+
+        for (buffer, label) in self.buffers.iter().zip(self.buffer_labels.iter()) {
+            let size = buffer.size();
+            stats.buffer_bytes += size;
+            *stats
+                .buffer_bytes_by_label
+                .entry(label.to_string())
+                .or_default() += size;
+        }
+
+        let textures = self
+            .textures
+            .iter()
+            .zip(self.texture_labels.iter().map(|e| e.as_ref()));
+        // the hidden targets windows draw into count too
+        let surface_targets = self
+            .surfaces
+            .values()
+            .flat_map(|e| e.targets())
+            .map(|(label, texture)| (texture, label));
+        for (texture, label) in textures.chain(surface_targets) {
+            let texture = &texture.texture;
+            let format = texture.format();
+            // depth formats only have a copy size per aspect
+            let texel_size = format
+                .block_copy_size(None)
+                .or_else(|| format.block_copy_size(Some(wgpu::TextureAspect::DepthOnly)))
+                .unwrap_or(4) as u64;
+            let size = texture.width() as u64
+                * texture.height() as u64
+                * texture.depth_or_array_layers() as u64
+                * texture.sample_count() as u64
+                * texel_size;
+            stats.texture_bytes += size;
+            *stats
+                .texture_bytes_by_label
+                .entry(label.to_string())
+                .or_default() += size;
+        }
+
+        stats
+    }
+}
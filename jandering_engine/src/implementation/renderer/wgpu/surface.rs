@@ -0,0 +1,375 @@
+use je_windowing::{FpsPreference, Window, WindowTrait};
+
+use crate::{
+    engine::EngineError,
+    renderer::{Janderer, TextureHandle},
+    surface::{AlphaMode, PresentMode, SurfaceCapabilities, SurfaceConfig},
+    texture::{Texture, TextureFormat, TextureSize},
+    types::UVec2,
+};
+
+use super::{Surface, WGPURenderer};
+
+const SURFACE_FORMATS: [TextureFormat; 3] = [
+    TextureFormat::Bgra8U,
+    TextureFormat::Rgba8U,
+    TextureFormat::Rgba16F,
+];
+
+impl Surface {
+    pub(super) fn new(renderer: &WGPURenderer, window: &Window) -> Result<Self, EngineError> {
+        let surface = renderer
+            .instance
+            .create_surface(window.shared())
+            .map_err(EngineError::CreateSurface)?;
+
+        let surface_capabilities = surface.get_capabilities(&renderer.adapter);
+
+        // only formats get_surface_config can report back
+        let formats = surface_capabilities
+            .formats
+            .iter()
+            .copied()
+            .filter(|f| {
+                SURFACE_FORMATS
+                    .into_iter()
+                    .any(|e| WGPURenderer::target_format(e) == *f)
+            })
+            .collect::<Vec<_>>();
+        let surface_format = formats
+            .iter()
+            .copied()
+            .find(|f| f.is_srgb())
+            .or(formats.first().copied())
+            .ok_or_else(|| {
+                EngineError::UnsupportedSurfaceFormats(surface_capabilities.formats.clone())
+            })?;
+
+        let fps_preference = window.get_fps_prefrence();
+
+        let (width, height) = window.size();
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: surface_format,
+            width,
+            height,
+            present_mode: fps_present_mode(fps_preference),
+            alpha_mode: surface_capabilities.alpha_modes[0],
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+
+        surface.configure(&renderer.device, &config);
+        Ok(Self {
+            surface,
+            config,
+            surface_texture: None,
+            fps_preference,
+            needs_configure: false,
+            skip_frame: false,
+            msaa_samples: 1,
+            msaa_texture: None,
+            depth_texture: None,
+        })
+    }
+
+    // the texture for this frame, changes to the config are applied before a new one is acquired.
+    // None skips the window's frame, surfaces get outdated on resizes and lost on gpu resets
+    pub(super) fn current_texture(
+        &mut self,
+        device: &wgpu::Device,
+        window: &Window,
+    ) -> Option<&wgpu::SurfaceTexture> {
+        if self.surface_texture.is_none() {
+            if self.skip_frame {
+                return None;
+            }
+
+            let fps_preference = window.get_fps_prefrence();
+            if fps_preference != self.fps_preference {
+                self.fps_preference = fps_preference;
+                self.config.present_mode = fps_present_mode(fps_preference);
+                self.needs_configure = true;
+            }
+
+            if self.needs_configure {
+                self.surface.configure(device, &self.config);
+                self.needs_configure = false;
+            }
+
+            match self.surface.get_current_texture() {
+                Ok(surface_texture) => self.surface_texture = Some(surface_texture),
+                Err(wgpu::SurfaceError::Outdated | wgpu::SurfaceError::Lost) => {
+                    // the window can be resized before resize() hears about it
+                    let (width, height) = window.size();
+                    if width != 0 && height != 0 {
+                        self.config.width = width;
+                        self.config.height = height;
+                        self.surface.configure(device, &self.config);
+                    }
+                    self.skip_frame = true;
+                }
+                Err(wgpu::SurfaceError::Timeout) => self.skip_frame = true,
+                Err(e) => panic!("{e}"),
+            }
+        }
+
+        self.surface_texture.as_ref()
+    }
+
+    pub(super) fn config(&self) -> SurfaceConfig {
+        SurfaceConfig {
+            present_mode: from_wgpu_present_mode(self.config.present_mode),
+            format: SURFACE_FORMATS
+                .into_iter()
+                .find(|e| WGPURenderer::target_format(*e) == self.config.format)
+                .unwrap_or_else(|| {
+                    panic!("surface format {:?} isn't supported", self.config.format)
+                }),
+            alpha_mode: from_wgpu_alpha_mode(self.config.alpha_mode),
+            frame_latency: self.config.desired_maximum_frame_latency,
+            msaa_samples: self.msaa_samples,
+        }
+    }
+
+    pub(super) fn capabilities(
+        &self,
+        adapter: &wgpu::Adapter,
+        device: &wgpu::Device,
+    ) -> SurfaceCapabilities {
+        let capabilities = self.surface.get_capabilities(adapter);
+        let formats = SURFACE_FORMATS
+            .into_iter()
+            .map(WGPURenderer::target_format)
+            .filter(|e| capabilities.formats.contains(e))
+            .chain(std::iter::once(wgpu::TextureFormat::Depth32Float))
+            .collect::<Vec<_>>();
+        SurfaceCapabilities {
+            present_modes: capabilities
+                .present_modes
+                .into_iter()
+                .map(from_wgpu_present_mode)
+                .collect(),
+            formats: SURFACE_FORMATS
+                .into_iter()
+                .filter(|e| {
+                    capabilities
+                        .formats
+                        .contains(&WGPURenderer::target_format(*e))
+                })
+                .collect(),
+            alpha_modes: capabilities
+                .alpha_modes
+                .into_iter()
+                .map(from_wgpu_alpha_mode)
+                .collect(),
+            msaa_samples: [1, 2, 4, 8, 16]
+                .into_iter()
+                .filter(|count| {
+                    formats.iter().all(|format| {
+                        // the device only gets what the adapter supports on top of the
+                        // guaranteed counts when it asked for it
+                        let features = if device
+                            .features()
+                            .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES)
+                        {
+                            adapter.get_texture_format_features(*format)
+                        } else {
+                            format.guaranteed_format_features(device.features())
+                        };
+                        features.flags.sample_count_supported(*count)
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    pub(super) fn set_config(
+        &mut self,
+        adapter: &wgpu::Adapter,
+        device: &wgpu::Device,
+        config: SurfaceConfig,
+    ) -> Result<(), EngineError> {
+        let capabilities = self.capabilities(adapter, device);
+        if !capabilities.supports(&config) {
+            return Err(EngineError::UnsupportedSurfaceConfig {
+                config,
+                capabilities,
+            });
+        }
+
+        self.config.present_mode = present_mode(config.present_mode);
+        self.config.format = WGPURenderer::target_format(config.format);
+        self.config.alpha_mode = alpha_mode(config.alpha_mode);
+        self.config.desired_maximum_frame_latency = config.frame_latency;
+        // the hidden targets follow the next time a pass uses them
+        self.msaa_samples = config.msaa_samples;
+        // the frame that's being drawn has to be presented first
+        self.needs_configure = true;
+        Ok(())
+    }
+
+    // (re)creates the hidden targets a pass needs when the surface's size, format or
+    // msaa changed since they were made
+    pub(super) fn prepare_targets(&mut self, device: &wgpu::Device, depth: bool) {
+        if self.msaa_samples > 1 {
+            let format = self.config.format;
+            if !self.is_target_current(&self.msaa_texture, format) {
+                self.msaa_texture = Some(self.create_target(device, format, "msaa color"));
+            }
+        } else {
+            self.msaa_texture = None;
+        }
+
+        let format = wgpu::TextureFormat::Depth32Float;
+        if depth && !self.is_target_current(&self.depth_texture, format) {
+            self.depth_texture = Some(self.create_target(device, format, "screen depth"));
+        }
+    }
+
+    // what passes drawing to the screen render into and resolve to
+    pub(super) fn color_views<'a>(
+        &'a self,
+        surface_view: &'a wgpu::TextureView,
+    ) -> (&'a wgpu::TextureView, Option<&'a wgpu::TextureView>) {
+        match &self.msaa_texture {
+            Some(texture) => (&texture.view, Some(surface_view)),
+            None => (surface_view, None),
+        }
+    }
+
+    pub(super) fn depth_view(&self) -> &wgpu::TextureView {
+        &self.depth_texture.as_ref().unwrap().view
+    }
+
+    pub(super) fn drop_targets(&mut self) {
+        self.msaa_texture = None;
+        self.depth_texture = None;
+    }
+
+    pub(super) fn targets(&self) -> impl Iterator<Item = (&'static str, &Texture)> {
+        let msaa = self.msaa_texture.iter().map(|e| ("msaa color", e));
+        let depth = self.depth_texture.iter().map(|e| ("screen depth", e));
+        msaa.chain(depth)
+    }
+
+    fn is_target_current(&self, texture: &Option<Texture>, format: wgpu::TextureFormat) -> bool {
+        texture.as_ref().map_or(false, |e| {
+            e.width == self.config.width
+                && e.height == self.config.height
+                && e.texture.format() == format
+                && e.texture.sample_count() == self.msaa_samples
+        })
+    }
+
+    fn create_target(
+        &self,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        label: &'static str,
+    ) -> Texture {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width: self.config.width,
+                height: self.config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: self.msaa_samples,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        Texture {
+            texture,
+            view,
+            width: self.config.width,
+            height: self.config.height,
+        }
+    }
+}
+
+impl WGPURenderer {
+    pub(super) fn screen_texture_size(&self, window: je_windowing::WindowId, scale: f32) -> UVec2 {
+        match self.surfaces.get(&window) {
+            Some(surface) => UVec2::new(
+                ((surface.config.width as f32 * scale) as u32).max(1),
+                ((surface.config.height as f32 * scale) as u32).max(1),
+            ),
+            None => UVec2::ONE,
+        }
+    }
+
+    // textures sized to a window follow its surface, bind groups holding the old ones get
+    // rebuilt before the next pass
+    pub(super) fn resize_screen_textures(&mut self) {
+        let resized = self
+            .screen_textures
+            .iter()
+            .filter(|(index, desc)| {
+                let TextureSize::Screen(window, scale) = desc.size else {
+                    unreachable!()
+                };
+                let texture = &self.textures[**index];
+                UVec2::new(texture.width, texture.height) != self.screen_texture_size(window, scale)
+            })
+            .map(|(index, desc)| (TextureHandle(*index), desc.clone()))
+            .collect::<Vec<_>>();
+
+        for (handle, desc) in resized {
+            self.create_texture_at(desc, handle);
+        }
+    }
+}
+
+fn fps_present_mode(fps_preference: FpsPreference) -> wgpu::PresentMode {
+    match fps_preference {
+        FpsPreference::Vsync => wgpu::PresentMode::AutoVsync,
+        FpsPreference::Exact(_) | FpsPreference::Uncapped => wgpu::PresentMode::AutoNoVsync,
+    }
+}
+
+fn present_mode(present_mode: PresentMode) -> wgpu::PresentMode {
+    match present_mode {
+        PresentMode::AutoVsync => wgpu::PresentMode::AutoVsync,
+        PresentMode::AutoNoVsync => wgpu::PresentMode::AutoNoVsync,
+        PresentMode::Fifo => wgpu::PresentMode::Fifo,
+        PresentMode::FifoRelaxed => wgpu::PresentMode::FifoRelaxed,
+        PresentMode::Mailbox => wgpu::PresentMode::Mailbox,
+        PresentMode::Immediate => wgpu::PresentMode::Immediate,
+    }
+}
+
+fn from_wgpu_present_mode(present_mode: wgpu::PresentMode) -> PresentMode {
+    match present_mode {
+        wgpu::PresentMode::AutoVsync => PresentMode::AutoVsync,
+        wgpu::PresentMode::AutoNoVsync => PresentMode::AutoNoVsync,
+        wgpu::PresentMode::Fifo => PresentMode::Fifo,
+        wgpu::PresentMode::FifoRelaxed => PresentMode::FifoRelaxed,
+        wgpu::PresentMode::Mailbox => PresentMode::Mailbox,
+        wgpu::PresentMode::Immediate => PresentMode::Immediate,
+    }
+}
+
+fn alpha_mode(alpha_mode: AlphaMode) -> wgpu::CompositeAlphaMode {
+    match alpha_mode {
+        AlphaMode::Auto => wgpu::CompositeAlphaMode::Auto,
+        AlphaMode::Opaque => wgpu::CompositeAlphaMode::Opaque,
+        AlphaMode::PreMultiplied => wgpu::CompositeAlphaMode::PreMultiplied,
+        AlphaMode::PostMultiplied => wgpu::CompositeAlphaMode::PostMultiplied,
+        AlphaMode::Inherit => wgpu::CompositeAlphaMode::Inherit,
+    }
+}
+
+fn from_wgpu_alpha_mode(alpha_mode: wgpu::CompositeAlphaMode) -> AlphaMode {
+    match alpha_mode {
+        wgpu::CompositeAlphaMode::Auto => AlphaMode::Auto,
+        wgpu::CompositeAlphaMode::Opaque => AlphaMode::Opaque,
+        wgpu::CompositeAlphaMode::PreMultiplied => AlphaMode::PreMultiplied,
+        wgpu::CompositeAlphaMode::PostMultiplied => AlphaMode::PostMultiplied,
+        wgpu::CompositeAlphaMode::Inherit => AlphaMode::Inherit,
+    }
+}
@@ -0,0 +1,247 @@
+use je_windowing::{Window, WindowTrait};
+
+use crate::{
+    bind_group::BindGroupLayoutDescriptor,
+    render_pass::{RenderAction, RenderStep},
+    renderer::{ShaderHandle, TargetTexture},
+    shader::reflection::describe_entry,
+};
+
+use super::WGPURenderer;
+
+impl WGPURenderer {
+    // catches what would otherwise be an index panic or a wgpu validation error
+    // pointing at the encoder, runs before anything is encoded
+    pub(super) fn validate_pass(
+        &self,
+        window: &Window,
+        steps: &[RenderStep],
+    ) -> Result<(), String> {
+        // the last step is the one the next render call would fill in
+        let len = steps.len() - 1;
+        for (i, step) in steps.iter().enumerate().take(len) {
+            self.validate_step(window, step)
+                .map_err(|err| format!("step {i}: {err}"))?;
+        }
+        Ok(())
+    }
+
+    fn validate_step(&self, window: &Window, step: &RenderStep) -> Result<(), String> {
+        let color_format = match step.target {
+            TargetTexture::Screen => Some(self.surfaces[&window.id()].config.format),
+            TargetTexture::Handle(handle) => Some(self.texture_format(handle.0, "target")?),
+            TargetTexture::None => None,
+        };
+        if let Some(TargetTexture::Handle(handle)) = step.resolve_target {
+            self.texture_format(handle.0, "resolve target")?;
+        }
+        let depth_format = match step.depth_tex {
+            Some(TargetTexture::Screen) => Some(wgpu::TextureFormat::Depth32Float),
+            Some(TargetTexture::Handle(handle)) => Some(self.texture_format(handle.0, "depth")?),
+            Some(TargetTexture::None) | None => None,
+        };
+
+        // wgpu needs every attachment of a pass to have the same size and sample count
+        let depth = step.depth_tex.and_then(|e| self.attachment(window, e));
+        if let (Some(color), Some(depth)) = (self.attachment(window, step.target), depth) {
+            if color != depth {
+                return Err(format!(
+                    "the target is {} but the depth texture is {}",
+                    describe_attachment(color),
+                    describe_attachment(depth)
+                ));
+            }
+        }
+
+        for (slot, handle) in step.bind_groups.iter() {
+            if handle.0 >= self.bind_groups.len() {
+                return Err(format!(
+                    "bind group {} at slot {slot} doesn't exist, there are {}",
+                    handle.0,
+                    self.bind_groups.len()
+                ));
+            }
+        }
+
+        if let RenderAction::Mesh {
+            vertex_buffer_handles,
+            index_buffer_handle,
+            ..
+        } = &step.action
+        {
+            let buffers = vertex_buffer_handles
+                .iter()
+                .map(|e| (e, wgpu::BufferUsages::VERTEX, "vertex"))
+                .chain([(index_buffer_handle, wgpu::BufferUsages::INDEX, "index")]);
+            for (handle, usage, what) in buffers {
+                let Some(buffer) = self.buffers.get(handle.index) else {
+                    return Err(format!(
+                        "buffer {} doesn't exist, there are {}",
+                        handle.index,
+                        self.buffers.len()
+                    ));
+                };
+                if !buffer.usage().contains(usage) {
+                    return Err(format!(
+                        "buffer {} \"{}\" is used as a {what} buffer but has usage {:?}",
+                        handle.index,
+                        self.buffer_labels[handle.index],
+                        buffer.usage()
+                    ));
+                }
+            }
+        }
+
+        let Some(shader) = step.shader else {
+            return match step.action {
+                RenderAction::Mesh { .. } => Err("draws without a shader set".to_string()),
+                RenderAction::Empty => Ok(()),
+            };
+        };
+        if shader.0 >= self.shaders.len() {
+            return Err(format!(
+                "shader {} doesn't exist, there are {}",
+                shader.0,
+                self.shaders.len()
+            ));
+        }
+
+        self.validate_shader(shader, step, color_format, depth_format)
+            .map_err(|err| {
+                format!(
+                    "shader \"{}\": {err}",
+                    self.shader_descriptors[shader.0].name
+                )
+            })
+    }
+
+    fn validate_shader(
+        &self,
+        shader: ShaderHandle,
+        step: &RenderStep,
+        color_format: Option<wgpu::TextureFormat>,
+        depth_format: Option<wgpu::TextureFormat>,
+    ) -> Result<(), String> {
+        // shaders without a compiled variant yet get theirs when the pass is submitted,
+        // their layouts can only come from the descriptor until then
+        let desc = self.shaders[shader.0]
+            .compiled
+            .get(&step.variant)
+            .map(|e| &e.resolved)
+            .unwrap_or(&self.shader_descriptors[shader.0]);
+
+        if step.variant.0 >> desc.features.len() != 0 {
+            return Err(format!(
+                "variant {:#b} enables features past the ones it has, {:?}",
+                step.variant.0, desc.features
+            ));
+        }
+
+        let expected = desc.target_texture_format.map(Self::target_format);
+        if expected != color_format {
+            return Err(format!(
+                "draws to {expected:?} but the target is {color_format:?}"
+            ));
+        }
+
+        match (desc.depth, depth_format) {
+            (true, None) => return Err("tests depth but no depth texture is set".to_string()),
+            (false, Some(_)) => {
+                return Err("doesn't test depth but a depth texture is set".to_string())
+            }
+            (true, Some(format)) if format != wgpu::TextureFormat::Depth32Float => {
+                return Err(format!(
+                    "expects a Depth32Float depth texture, given {format:?}"
+                ))
+            }
+            _ => {}
+        }
+
+        let layouts = &desc.bind_group_layout_descriptors;
+
+        for (slot, handle) in step.bind_groups.iter() {
+            let given = BindGroupLayoutDescriptor::from(&self.bind_groups[handle.0].get_layout());
+            let Some(expected) = layouts.get(*slot as usize) else {
+                return Err(format!(
+                    "has {} bind groups, given {} at slot {slot}",
+                    layouts.len(),
+                    describe_layout(&given)
+                ));
+            };
+            if *expected != given {
+                return Err(format!(
+                    "expects {} at slot {slot}, given {}",
+                    describe_layout(expected),
+                    describe_layout(&given)
+                ));
+            }
+        }
+
+        if let RenderAction::Mesh {
+            vertex_buffer_handles,
+            ..
+        } = &step.action
+        {
+            for (slot, expected) in layouts.iter().enumerate() {
+                if !expected.entries.is_empty() && !step.bind_groups.contains_key(&(slot as u32)) {
+                    return Err(format!(
+                        "expects {} at slot {slot}, none is bound",
+                        describe_layout(expected)
+                    ));
+                }
+            }
+
+            let expected = desc.descriptors.len();
+            if vertex_buffer_handles.len() < expected {
+                return Err(format!(
+                    "expects {expected} vertex buffers, given {}",
+                    vertex_buffer_handles.len()
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    // samples, width and height of a target that's known to exist
+    fn attachment(&self, window: &Window, target: TargetTexture) -> Option<(u32, u32, u32)> {
+        match target {
+            TargetTexture::Screen => {
+                let surface = &self.surfaces[&window.id()];
+                Some((
+                    surface.msaa_samples,
+                    surface.config.width,
+                    surface.config.height,
+                ))
+            }
+            TargetTexture::Handle(handle) => {
+                let texture = &self.textures[handle.0].texture;
+                Some((texture.sample_count(), texture.width(), texture.height()))
+            }
+            TargetTexture::None => None,
+        }
+    }
+
+    fn texture_format(&self, index: usize, what: &str) -> Result<wgpu::TextureFormat, String> {
+        match self.textures.get(index) {
+            Some(texture) => Ok(texture.texture.format()),
+            None => Err(format!(
+                "{what} texture {index} doesn't exist, there are {}",
+                self.textures.len()
+            )),
+        }
+    }
+}
+
+fn describe_attachment((samples, width, height): (u32, u32, u32)) -> String {
+    format!("{width}x{height} with {samples} samples")
+}
+
+fn describe_layout(layout: &BindGroupLayoutDescriptor) -> String {
+    let entries = layout
+        .entries
+        .iter()
+        .map(describe_entry)
+        .collect::<Vec<_>>();
+    format!("[{}]", entries.join(", "))
+}
@@ -1,4 +1,8 @@
+// lets derive macros refer to ::jandering_engine from inside this crate too
+extern crate self as jandering_engine;
+
 pub mod bind_group;
+pub mod buffer;
 pub mod compute_pass;
 pub mod engine;
 pub mod implementation;
@@ -6,7 +10,10 @@ pub mod object;
 pub mod render_pass;
 pub mod renderer;
 pub mod shader;
+pub mod stats;
+pub mod surface;
 pub mod texture;
 pub mod types;
 pub mod utils;
 pub use je_windowing as window;
+pub use wgpu;
@@ -2,22 +2,19 @@ use primitives::plane_data;
 
 use crate::{
     renderer::{BufferHandle, Janderer},
-    shader::BufferLayoutEntryDataType,
+    shader::VertexLayout,
     types::*,
     utils::load_obj,
 };
 
 use self::primitives::{quad_data, triangle_data};
 
-use super::{
-    renderer::Renderer,
-    shader::{BufferLayout, BufferLayoutEntry},
-};
+use super::renderer::Renderer;
 
 pub mod primitives;
 
 #[repr(C)]
-#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Debug)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Debug, VertexLayout)]
 pub struct Vertex {
     pub position: Vec3,
     pub normal: Vec3,
@@ -42,33 +39,13 @@ pub struct Object<T> {
     //
     pub render_data: ObjectRenderData,
 
-    previous_instances_len: usize,
-}
-
-impl Vertex {
-    pub fn desc() -> BufferLayout {
-        BufferLayout {
-            step_mode: crate::shader::BufferLayoutStepMode::Vertex,
-            entries: &[
-                BufferLayoutEntry {
-                    location: 0,
-                    data_type: BufferLayoutEntryDataType::Float32x3,
-                },
-                BufferLayoutEntry {
-                    location: 1,
-                    data_type: BufferLayoutEntryDataType::Float32x3,
-                },
-                BufferLayoutEntry {
-                    location: 2,
-                    data_type: BufferLayoutEntryDataType::Float32x2,
-                },
-            ],
-        }
-    }
+    // what the instance buffer holds, update only writes the instances that differ from it
+    uploaded_instances: Vec<T>,
 }
 
 #[repr(C)]
-#[derive(Copy, Debug, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Copy, Debug, Clone, bytemuck::Pod, bytemuck::Zeroable, VertexLayout)]
+#[layout(step = instance, start_location = 5)]
 pub struct Instance {
     pub model: Mat4,
     pub inv_model: Mat4,
@@ -84,46 +61,6 @@ impl Default for Instance {
 }
 
 impl Instance {
-    pub fn desc() -> BufferLayout {
-        BufferLayout {
-            step_mode: crate::shader::BufferLayoutStepMode::Instance,
-            entries: &[
-                BufferLayoutEntry {
-                    location: 5,
-                    data_type: BufferLayoutEntryDataType::Float32x4,
-                },
-                BufferLayoutEntry {
-                    location: 6,
-                    data_type: BufferLayoutEntryDataType::Float32x4,
-                },
-                BufferLayoutEntry {
-                    location: 7,
-                    data_type: BufferLayoutEntryDataType::Float32x4,
-                },
-                BufferLayoutEntry {
-                    location: 8,
-                    data_type: BufferLayoutEntryDataType::Float32x4,
-                },
-                BufferLayoutEntry {
-                    location: 9,
-                    data_type: BufferLayoutEntryDataType::Float32x4,
-                },
-                BufferLayoutEntry {
-                    location: 10,
-                    data_type: BufferLayoutEntryDataType::Float32x4,
-                },
-                BufferLayoutEntry {
-                    location: 11,
-                    data_type: BufferLayoutEntryDataType::Float32x4,
-                },
-                BufferLayoutEntry {
-                    location: 12,
-                    data_type: BufferLayoutEntryDataType::Float32x4,
-                },
-            ],
-        }
-    }
-
     pub fn from_mat(model: Mat4) -> Self {
         Self {
             model,
@@ -229,28 +166,75 @@ impl<T: bytemuck::Pod> Object<T> {
             }
         };
 
-        let previous_instances_len = instances.len();
+        let uploaded_instances = instances.clone();
 
         Object {
             vertices,
             indices,
             instances,
             render_data,
-            previous_instances_len,
+            uploaded_instances,
         }
     }
 
     pub fn update(&mut self, renderer: &mut Renderer) {
-        if self.previous_instances_len != self.instances.len() {
-            self.render_data.instance_buffer =
-                renderer.create_vertex_buffer(bytemuck::cast_slice(&self.instances));
-            self.previous_instances_len = self.instances.len();
-        } else {
-            renderer.write_buffer(
-                self.render_data.instance_buffer,
-                bytemuck::cast_slice(&self.instances),
-            );
+        let size = std::mem::size_of::<T>();
+
+        // every instance gets written anyway, so the old contents aren't copied over
+        if self.uploaded_instances.len() != self.instances.len() {
+            let data = bytemuck::cast_slice(&self.instances);
+            renderer.replace_buffer(self.render_data.instance_buffer, data);
+            self.uploaded_instances.clone_from(&self.instances);
+            return;
+        }
+
+        // writes have to be 4 byte aligned, instances that aren't go up whole
+        if size as u64 % wgpu::COPY_BUFFER_ALIGNMENT != 0 {
+            let data = bytemuck::cast_slice(&self.instances);
+            renderer.write_buffer(self.render_data.instance_buffer, data);
+            self.uploaded_instances.clone_from(&self.instances);
+            return;
         }
+
+        // one write per run of changed instances
+        let mut start = None;
+        for i in 0..=self.instances.len() {
+            let changed = i < self.instances.len()
+                && bytemuck::bytes_of(&self.instances[i])
+                    != bytemuck::bytes_of(&self.uploaded_instances[i]);
+            match (changed, start) {
+                (true, None) => start = Some(i),
+                (false, Some(first)) => {
+                    let range = first..i;
+                    renderer.write_buffer_at(
+                        self.render_data.instance_buffer,
+                        (first * size) as u64,
+                        bytemuck::cast_slice(&self.instances[range.clone()]),
+                    );
+                    self.uploaded_instances[range.clone()].copy_from_slice(&self.instances[range]);
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // writes the vertices, indices and every instance again, like after the device was lost
+    pub fn upload(&mut self, renderer: &mut Renderer) {
+        let render_data = &self.render_data;
+        renderer.write_buffer(
+            render_data.vertex_buffer,
+            bytemuck::cast_slice(&self.vertices),
+        );
+        renderer.write_buffer(
+            render_data.index_buffer,
+            bytemuck::cast_slice(&self.indices),
+        );
+        renderer.replace_buffer(
+            render_data.instance_buffer,
+            bytemuck::cast_slice(&self.instances),
+        );
+        self.uploaded_instances.clone_from(&self.instances);
     }
 
     pub fn from_obj(data: &str, renderer: &mut Renderer, instances: Vec<T>) -> Object<T> {
@@ -284,7 +268,8 @@ impl<T: bytemuck::Pod> Object<T> {
 }
 
 #[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, VertexLayout)]
+#[layout(step = instance, start_location = 3)]
 pub struct D2Instance {
     pub position: Vec2,
     pub scale: Vec2,
@@ -304,29 +289,6 @@ impl Default for D2Instance {
 }
 
 impl D2Instance {
-    pub fn desc() -> BufferLayout {
-        BufferLayout {
-            step_mode: crate::shader::BufferLayoutStepMode::Instance,
-            entries: &[
-                BufferLayoutEntry {
-                    location: 3,
-                    data_type: BufferLayoutEntryDataType::Float32x2,
-                },
-                BufferLayoutEntry {
-                    location: 4,
-                    data_type: BufferLayoutEntryDataType::Float32x2,
-                },
-                BufferLayoutEntry {
-                    location: 5,
-                    data_type: BufferLayoutEntryDataType::Float32,
-                },
-                BufferLayoutEntry {
-                    location: 6,
-                    data_type: BufferLayoutEntryDataType::Float32x3,
-                },
-            ],
-        }
-    }
     pub fn set_color(mut self, color: Vec3) -> Self {
         self.color = color;
         self
@@ -359,7 +321,10 @@ impl D2Instance {
 }
 
 pub trait Renderable {
-    fn get_buffers(&self) -> (BufferHandle, BufferHandle, Option<BufferHandle>);
+    // one buffer per BufferLayout of the shader, in the same order
+    fn get_vertex_buffers(&self) -> Vec<BufferHandle>;
+
+    fn get_index_buffer(&self) -> BufferHandle;
 
     fn num_indices(&self) -> u32;
 
@@ -368,19 +333,22 @@ pub trait Renderable {
 
 impl<T: std::any::Any> Renderable for Object<T> {
     fn num_instances(&self) -> u32 {
-        self.previous_instances_len as u32
+        self.uploaded_instances.len() as u32
     }
 
     fn num_indices(&self) -> u32 {
         self.indices.len() as u32
     }
 
-    fn get_buffers(&self) -> (BufferHandle, BufferHandle, Option<BufferHandle>) {
-        (
+    fn get_vertex_buffers(&self) -> Vec<BufferHandle> {
+        vec![
             self.render_data.vertex_buffer,
-            self.render_data.index_buffer,
-            Some(self.render_data.instance_buffer),
-        )
+            self.render_data.instance_buffer,
+        ]
+    }
+
+    fn get_index_buffer(&self) -> BufferHandle {
+        self.render_data.index_buffer
     }
 }
 
@@ -2,7 +2,10 @@ use std::{collections::HashMap, ops::Range};
 
 use crate::{
     object::Renderable,
-    renderer::{BufferHandle, ShaderHandle, TargetTexture, TextureHandle, UntypedBindGroupHandle},
+    renderer::{
+        BufferHandle, ShaderHandle, ShaderVariant, TargetTexture, TextureHandle,
+        UntypedBindGroupHandle,
+    },
     types::Vec3,
 };
 
@@ -11,9 +14,9 @@ use je_windowing::Window;
 #[derive(Clone, Default, Debug)]
 pub enum RenderAction {
     Mesh {
-        vertex_buffer_handle: BufferHandle,
+        // bound to vertex buffer slots in order
+        vertex_buffer_handles: Vec<BufferHandle>,
         index_buffer_handle: BufferHandle,
-        instance_buffer_handle: Option<BufferHandle>,
         range: Range<u32>,
         num_indices: u32,
     },
@@ -26,10 +29,12 @@ pub struct RenderStep {
     pub(crate) action: RenderAction,
 
     pub(crate) shader: Option<ShaderHandle>,
+    pub(crate) variant: ShaderVariant,
     pub(crate) bind_groups: HashMap<u32, UntypedBindGroupHandle>,
 
     pub(crate) target: TargetTexture,
-    pub(crate) depth_tex: Option<TextureHandle>,
+    // Screen is the window's own depth, sized and multisampled to match it
+    pub(crate) depth_tex: Option<TargetTexture>,
     pub(crate) resolve_target: Option<TargetTexture>,
 
     pub(crate) alpha: f32,
@@ -42,6 +47,7 @@ impl Default for RenderStep {
         Self {
             action: Default::default(),
             shader: Default::default(),
+            variant: Default::default(),
             bind_groups: Default::default(),
             target: Default::default(),
             depth_tex: Default::default(),
@@ -91,13 +97,12 @@ impl<'renderer> RenderPass<'renderer> {
 
     pub fn render_range(mut self, renderable: &impl Renderable, range: Range<u32>) -> Self {
         let step = self.steps.last_mut().unwrap();
-        let (vertex_buffer_handle, index_buffer_handle, instance_buffer_handle) =
-            renderable.get_buffers();
+        let vertex_buffer_handles = renderable.get_vertex_buffers();
+        let index_buffer_handle = renderable.get_index_buffer();
         let num_indices = renderable.num_indices();
         step.action = RenderAction::Mesh {
-            vertex_buffer_handle,
+            vertex_buffer_handles,
             index_buffer_handle,
-            instance_buffer_handle,
             range,
             num_indices,
         };
@@ -125,14 +130,28 @@ impl<'renderer> RenderPass<'renderer> {
         self
     }
 
-    pub fn set_shader(mut self, shader: ShaderHandle) -> Self {
-        self.steps.last_mut().unwrap().shader = Some(shader);
+    pub fn set_shader(self, shader: ShaderHandle) -> Self {
+        self.set_shader_variant(shader, ShaderVariant::default())
+    }
+
+    // the variant has to come from get_shader_variant so its pipeline exists
+    pub fn set_shader_variant(mut self, shader: ShaderHandle, variant: ShaderVariant) -> Self {
+        let step = self.steps.last_mut().unwrap();
+        step.shader = Some(shader);
+        step.variant = variant;
         self
     }
 
     pub fn with_depth(mut self, texture: TextureHandle, value: Option<f32>) -> Self {
         let data = self.steps.last_mut().unwrap();
-        data.depth_tex = Some(texture);
+        data.depth_tex = Some(TargetTexture::Handle(texture));
+        data.depth = value;
+        self
+    }
+
+    pub fn with_screen_depth(mut self, value: Option<f32>) -> Self {
+        let data = self.steps.last_mut().unwrap();
+        data.depth_tex = Some(TargetTexture::Screen);
         data.depth = value;
         self
     }
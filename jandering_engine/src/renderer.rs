@@ -1,8 +1,15 @@
 use crate::{
-    engine::EngineConfig,
+    buffer::{buffer_usage, BufferDescriptor, BufferError},
+    engine::{EngineConfig, EngineError},
     implementation::renderer::wgpu::{compute_pass::WGPUComputePass, WGPURenderer},
     render_pass::RenderPass,
-    shader::ComputeShaderDescriptor,
+    shader::{
+        frontend::ShaderCompileError,
+        layout::{DataLayout, ShaderType},
+        ComputeShaderDescriptor,
+    },
+    stats::RendererStats,
+    surface::{SurfaceCapabilities, SurfaceConfig},
 };
 
 use super::{
@@ -11,12 +18,51 @@ use super::{
     texture::{sampler::SamplerDescriptor, Texture, TextureDescriptor},
 };
 
-use je_windowing::Window;
+use je_windowing::{Window, WindowId};
 
 #[derive(Copy, Clone, Debug)]
 pub(crate) enum BufferType {
     Uniform,
     Storage,
+    Vertex,
+    Index,
+    Other,
+}
+
+impl BufferType {
+    // buffers with several usages are typed by how they'd be bound, which has to be one way.
+    // also rejects the usages wgpu would refuse to map
+    pub(crate) fn from_usage(usage: buffer_usage::BufferUsage) -> Result<Self, BufferError> {
+        let both = buffer_usage::UNIFORM | buffer_usage::STORAGE;
+        if usage & both == both {
+            return Err(BufferError::UniformAndStorage);
+        }
+
+        let map_read = usage & buffer_usage::MAP_READ != buffer_usage::NONE;
+        let map_write = usage & buffer_usage::MAP_WRITE != buffer_usage::NONE;
+        let allowed = match (map_read, map_write) {
+            (false, false) => usage,
+            (true, false) => buffer_usage::MAP_READ | buffer_usage::COPY_TARGET,
+            (false, true) => buffer_usage::MAP_WRITE | buffer_usage::COPY_SRC,
+            (true, true) => buffer_usage::NONE,
+        };
+        if usage & !allowed != buffer_usage::NONE {
+            return Err(BufferError::MapUsage(usage));
+        }
+
+        let buffer_type = if usage & buffer_usage::UNIFORM != buffer_usage::NONE {
+            Self::Uniform
+        } else if usage & buffer_usage::STORAGE != buffer_usage::NONE {
+            Self::Storage
+        } else if usage & buffer_usage::VERTEX != buffer_usage::NONE {
+            Self::Vertex
+        } else if usage & buffer_usage::INDEX != buffer_usage::NONE {
+            Self::Index
+        } else {
+            Self::Other
+        };
+        Ok(buffer_type)
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -55,6 +101,10 @@ pub struct UntypedBindGroupHandle(pub usize);
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct ShaderHandle(pub usize);
 
+// bit i enables the i-th feature of the shader's descriptor, 0 is the shader without features
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ShaderVariant(pub u64);
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct ComputeShaderHandle(pub usize);
 
@@ -76,56 +126,148 @@ pub enum TargetTexture {
     None,
 }
 
-pub trait Janderer {
+pub trait Janderer: Sized {
     #[allow(async_fn_in_trait)]
     #[allow(opaque_hidden_inferred_bound)]
-    async fn new(config: EngineConfig) -> Self;
+    async fn new(config: EngineConfig) -> Result<Self, EngineError>;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn adapters(backends: wgpu::Backends) -> Vec<wgpu::AdapterInfo>;
 
-    fn register_window(&mut self, window: &Window);
+    // creates the window's surface, fails when the adapter can't draw to it
+    fn register_window(&mut self, window: &Window) -> Result<(), EngineError>;
+
+    // the engine calls this for windows that closed
+    fn unregister_window(&mut self, window: WindowId);
 
     fn resize(&mut self, window: &Window, width: u32, height: u32);
 
+    fn get_surface_config(&self, window: &Window) -> SurfaceConfig;
+
+    fn get_surface_capabilities(&self, window: &Window) -> SurfaceCapabilities;
+
+    // takes effect from the window's next frame, nothing changes if the surface doesn't support it
+    fn set_surface_config(
+        &mut self,
+        window: &Window,
+        config: SurfaceConfig,
+    ) -> Result<(), EngineError>;
+
     // rendering
-    fn new_compute_pass(&mut self) -> ComputePass;
+    fn new_compute_pass(&mut self) -> ComputePass<'_>;
 
     fn submit_pass(&mut self, pass: RenderPass);
 
     fn present(&mut self);
 
+    fn stats(&self) -> RendererStats;
+
     // buffers
+    fn create_buffer(&mut self, desc: BufferDescriptor) -> Result<BufferHandle, BufferError>;
+
     fn create_uniform_buffer(&mut self, contents: &[u8]) -> BufferHandle;
 
+    // STORAGE and COPY_TARGET, create_buffer takes the others like INDIRECT or COPY_SRC
     fn create_storage_buffer_with_size(&mut self, size: usize) -> BufferHandle;
 
     fn create_storage_buffer(&mut self, contents: &[u8]) -> BufferHandle;
 
+    // debug builds check T against the wgsl struct the buffer gets bound to
+    fn create_typed_uniform_buffer<T: DataLayout + bytemuck::Pod>(
+        &mut self,
+        data: &T,
+    ) -> BufferHandle;
+
+    // one T per element of the wgsl array
+    fn create_typed_storage_buffer<T: DataLayout + bytemuck::Pod>(
+        &mut self,
+        data: &[T],
+    ) -> BufferHandle;
+
+    // for #[derive(ShaderType)] structs, written with data.std140_bytes() and checked like
+    // create_typed_uniform_buffer
+    fn create_std140_uniform_buffer<T: ShaderType>(&mut self, data: &T) -> BufferHandle;
+
     fn create_vertex_buffer(&mut self, contents: &[u8]) -> BufferHandle;
 
     fn create_index_buffer(&mut self, contents: &[u8]) -> BufferHandle;
 
+    // the returned buffer is only valid until present(), after which it gets reused
+    fn create_transient_buffer(
+        &mut self,
+        contents: &[u8],
+        usage: buffer_usage::BufferUsage,
+    ) -> Result<BufferHandle, BufferError>;
+
     fn write_buffer(&mut self, buffer: BufferHandle, data: &[u8]);
 
+    fn write_buffer_at(&mut self, buffer: BufferHandle, offset: u64, data: &[u8]);
+
+    // submitted on its own right away, so it sees earlier writes and passes but not later ones.
+    // every call is a separate submit, copy bigger ranges instead of many small ones
+    fn copy_buffer_to_buffer(
+        &mut self,
+        source: BufferHandle,
+        source_offset: u64,
+        destination: BufferHandle,
+        destination_offset: u64,
+        size: u64,
+    );
+
+    // keeps the handle valid and updates bind groups that use the buffer
+    fn resize_buffer(&mut self, buffer: BufferHandle, size: usize);
+
+    // like resize_buffer but the new buffer starts out with contents, nothing is copied over
+    fn replace_buffer(&mut self, buffer: BufferHandle, contents: &[u8]);
+
     //shaders
-    fn create_shader_at(&mut self, desc: ShaderDescriptor, handle: ShaderHandle);
+    // errors leave whatever was at the handle before in place
+    fn create_shader_at(
+        &mut self,
+        desc: ShaderDescriptor,
+        handle: ShaderHandle,
+    ) -> Result<(), ShaderCompileError>;
+
+    fn create_shader(&mut self, desc: ShaderDescriptor)
+        -> Result<ShaderHandle, ShaderCompileError>;
+
+    fn reload_shader(&mut self, handle: ShaderHandle) -> Result<(), ShaderCompileError>;
 
-    fn create_shader(&mut self, desc: ShaderDescriptor) -> ShaderHandle;
+    // stops at the first shader that fails, the ones before it are already reloaded
+    fn reload_shaders(&mut self) -> Result<(), ShaderCompileError>;
 
-    fn reload_shader(&mut self, handle: ShaderHandle);
+    // called after the device was lost and everything was recreated, buffers and textures
+    // come back zeroed so their contents have to be uploaded again
+    fn set_device_lost_callback(&mut self, callback: impl FnMut(&mut Self) + 'static);
 
-    fn reload_shaders(&mut self);
+    // also happens when the renderer is dropped, does nothing without EngineConfig::pipeline_cache_dir
+    fn save_pipeline_cache(&self);
+
+    // compiles the variant the first time it's asked for
+    fn get_shader_variant(
+        &mut self,
+        shader: ShaderHandle,
+        features: &[&str],
+    ) -> Result<ShaderVariant, ShaderCompileError>;
 
     // compute
     fn create_compute_shader_at(
         &mut self,
         desc: ComputeShaderDescriptor,
         handle: ComputeShaderHandle,
-    );
+    ) -> Result<(), ShaderCompileError>;
 
-    fn create_compute_shader(&mut self, desc: ComputeShaderDescriptor) -> ComputeShaderHandle;
+    fn create_compute_shader(
+        &mut self,
+        desc: ComputeShaderDescriptor,
+    ) -> Result<ComputeShaderHandle, ShaderCompileError>;
 
-    fn re_create_compute_shader(&mut self, handle: ComputeShaderHandle);
+    fn re_create_compute_shader(
+        &mut self,
+        handle: ComputeShaderHandle,
+    ) -> Result<(), ShaderCompileError>;
 
-    fn re_create_compute_shaders(&mut self);
+    fn re_create_compute_shaders(&mut self) -> Result<(), ShaderCompileError>;
 
     //textures
     fn create_texture_at(&mut self, desc: TextureDescriptor, handle: TextureHandle);
@@ -151,8 +293,12 @@ pub trait Janderer {
 
     fn get_bind_group(&self, handle: UntypedBindGroupHandle) -> Option<&dyn BindGroup>;
 
+    // changed handles are picked up before the next pass
     fn get_bind_group_mut(&mut self, handle: UntypedBindGroupHandle) -> Option<&mut dyn BindGroup>;
 
+    // rebuilds it right away, for resources changed in ways the renderer doesn't see
+    fn update_bind_group(&mut self, handle: UntypedBindGroupHandle);
+
     fn create_typed_bind_group_at<T: BindGroup>(
         &mut self,
         bind_group: T,
@@ -170,3 +316,48 @@ pub trait Janderer {
 
     // fn write_bind_group(&mut self, handle: UntypedBindGroupHandle, data: &[u8]);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use buffer_usage::*;
+
+    #[test]
+    fn buffer_type_from_usage() {
+        assert!(matches!(
+            BufferType::from_usage(UNIFORM | COPY_TARGET),
+            Ok(BufferType::Uniform)
+        ));
+        assert!(matches!(
+            BufferType::from_usage(STORAGE | VERTEX),
+            Ok(BufferType::Storage)
+        ));
+        assert!(matches!(
+            BufferType::from_usage(MAP_READ | COPY_TARGET),
+            Ok(BufferType::Other)
+        ));
+        assert!(matches!(
+            BufferType::from_usage(MAP_WRITE | COPY_SRC),
+            Ok(BufferType::Other)
+        ));
+    }
+
+    #[test]
+    fn buffer_type_from_invalid_usage() {
+        assert!(matches!(
+            BufferType::from_usage(UNIFORM | STORAGE),
+            Err(BufferError::UniformAndStorage)
+        ));
+        for usage in [
+            MAP_READ | STORAGE,
+            MAP_READ | COPY_SRC,
+            MAP_WRITE | COPY_TARGET,
+            MAP_READ | MAP_WRITE,
+        ] {
+            assert!(matches!(
+                BufferType::from_usage(usage),
+                Err(BufferError::MapUsage(e)) if e == usage
+            ));
+        }
+    }
+}
@@ -1,10 +1,6 @@
-struct Camera {
-    up: vec3<f32>,
-    right: vec3<f32>,
-    position: vec3<f32>,
-    direction: vec3<f32>,
-    view_proj: mat4x4<f32>,
-};
+#include "jandering_engine/camera.wgsl"
+#include "jandering_engine/vertex.wgsl"
+#include "jandering_engine/instance.wgsl"
 
 @group(0) @binding(0)
 var<uniform> camera: Camera;
@@ -14,24 +10,6 @@ var tex: texture_2d<f32>;
 @group(1) @binding(1)
 var tex_sampler: sampler;
 
-struct VertexInput{
-    @location(0) position: vec3<f32>,
-    @location(1) normal: vec3<f32>,
-    @location(2) uv: vec2<f32>,
-};
-
-struct InstanceInput{
-    @location(5) model_matrix_0: vec4<f32>,
-    @location(6) model_matrix_1: vec4<f32>,
-    @location(7) model_matrix_2: vec4<f32>,
-    @location(8) model_matrix_3: vec4<f32>,
-
-    @location(9)  inv_model_matrix_0: vec4<f32>,
-    @location(10) inv_model_matrix_1: vec4<f32>,
-    @location(11) inv_model_matrix_2: vec4<f32>,
-    @location(12) inv_model_matrix_3: vec4<f32>,
-}
-
 struct VertexOutput{
     @builtin(position) clip_position: vec4<f32>,
     @location(1) normal: vec3<f32>,
@@ -44,19 +22,8 @@ fn vs_main(
     instance: InstanceInput
 ) -> VertexOutput{
 
-    let model_matrix = mat4x4<f32>(
-        instance.model_matrix_0,
-        instance.model_matrix_1,
-        instance.model_matrix_2,
-        instance.model_matrix_3,
-    );
-
-    let inv_model_matrix = mat4x4<f32>(
-        instance.inv_model_matrix_0,
-        instance.inv_model_matrix_1,
-        instance.inv_model_matrix_2,
-        instance.inv_model_matrix_3,
-    );
+    let model_matrix = instance_model_matrix(instance);
+    let inv_model_matrix = instance_inv_model_matrix(instance);
 
     let world_position = model_matrix * vec4<f32>(model.position, 1.0);
     let normal = transpose(inv_model_matrix) * vec4<f32>(model.normal, 1.0);    
@@ -0,0 +1,331 @@
+use naga::ShaderStage;
+
+use super::{
+    preprocessor::{preprocess, PreprocessError},
+    reflection::ShaderLayoutError,
+    ShaderSource,
+};
+
+#[derive(Debug, Clone)]
+pub enum ShaderCompileError {
+    Preprocess(PreprocessError),
+    Glsl {
+        stage: ShaderStage,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    SpirV(String),
+    // glsl stages always start at `main`
+    GlslEntryPoint {
+        stage: ShaderStage,
+        entry: String,
+    },
+    GlslCompute,
+    Load {
+        name: String,
+        message: String,
+    },
+    Layout(ShaderLayoutError),
+    // variants are a u64 with a bit per feature
+    TooManyFeatures(usize),
+    UnknownFeatures {
+        features: Vec<String>,
+        requested: Vec<String>,
+    },
+}
+
+impl std::fmt::Display for ShaderCompileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShaderCompileError::Preprocess(err) => err.fmt(f),
+            ShaderCompileError::Glsl {
+                stage,
+                line,
+                column,
+                message,
+            } => write!(f, "{stage:?} glsl:{line}:{column}: {message}"),
+            ShaderCompileError::SpirV(message) => write!(f, "spir-v: {message}"),
+            ShaderCompileError::GlslEntryPoint { stage, entry } => write!(
+                f,
+                "{stage:?} glsl: the entry point is always `main`, the descriptor names `{entry}`"
+            ),
+            ShaderCompileError::GlslCompute => write!(
+                f,
+                "glsl sources only have vertex and fragment stages, compute has to be wgsl or spir-v"
+            ),
+            ShaderCompileError::Load { name, message } => {
+                write!(f, "couldn't load {name}: {message}")
+            }
+            ShaderCompileError::Layout(err) => err.fmt(f),
+            ShaderCompileError::TooManyFeatures(count) => {
+                write!(f, "declares {count} features, variants can only have 64")
+            }
+            ShaderCompileError::UnknownFeatures {
+                features,
+                requested,
+            } => write!(
+                f,
+                "asked for features {requested:?} but the shader only has {features:?}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ShaderCompileError {}
+
+impl From<PreprocessError> for ShaderCompileError {
+    fn from(err: PreprocessError) -> Self {
+        ShaderCompileError::Preprocess(err)
+    }
+}
+
+impl From<ShaderLayoutError> for ShaderCompileError {
+    fn from(err: ShaderLayoutError) -> Self {
+        ShaderCompileError::Layout(err)
+    }
+}
+
+pub(crate) enum CompiledShader {
+    // wgpu gets the preprocessed wgsl itself so it reports errors against the code it was given
+    Wgsl {
+        code: String,
+        module: naga::Module,
+    },
+    SpirV(naga::Module),
+    // glsl has one module per stage, both with a `main` entry point
+    Glsl {
+        vertex: Box<naga::Module>,
+        fragment: Box<naga::Module>,
+    },
+}
+
+impl CompiledShader {
+    pub(crate) fn entry_point<'a>(&self, entry: &'a str) -> &'a str {
+        match self {
+            CompiledShader::Glsl { .. } => "main",
+            _ => entry,
+        }
+    }
+
+    // the module and entry point behind each of the requested entries, in order
+    pub(crate) fn stages<'a>(&'a self, entries: &[&'a str]) -> Vec<(&'a naga::Module, &'a str)> {
+        match self {
+            CompiledShader::Wgsl { module, .. } | CompiledShader::SpirV(module) => {
+                entries.iter().map(|e| (module, *e)).collect()
+            }
+            CompiledShader::Glsl { vertex, fragment } => {
+                vec![(vertex.as_ref(), "main"), (fragment.as_ref(), "main")]
+            }
+        }
+    }
+}
+
+impl ShaderSource {
+    // loads and parses the source, `name` stands in for the file name of inline code.
+    // the entries are only checked for glsl, other sources report missing ones themselves
+    pub(crate) fn compile(
+        &self,
+        name: &str,
+        entries: &[(ShaderStage, &str)],
+        defines: &[(String, String)],
+    ) -> Result<CompiledShader, ShaderCompileError> {
+        if let ShaderSource::Glsl { .. } = self {
+            for (stage, entry) in entries {
+                if *stage == ShaderStage::Compute {
+                    return Err(ShaderCompileError::GlslCompute);
+                }
+                if *entry != "main" {
+                    return Err(ShaderCompileError::GlslEntryPoint {
+                        stage: *stage,
+                        entry: entry.to_string(),
+                    });
+                }
+            }
+        }
+
+        match self {
+            ShaderSource::Code(code) => compile_wgsl(code, name, defines),
+            #[cfg(not(target_arch = "wasm32"))]
+            ShaderSource::File(file_path) => {
+                let name = match file_path {
+                    crate::utils::FilePath::FileName(name) => name.to_string(),
+                    crate::utils::FilePath::OwnedFileName(name) => name.clone(),
+                    crate::utils::FilePath::AbsolutePath(path) => path.display().to_string(),
+                };
+                let code = pollster::block_on(crate::utils::load_text(file_path.clone())).map_err(
+                    |e| ShaderCompileError::Load {
+                        name: name.clone(),
+                        message: e.to_string(),
+                    },
+                )?;
+                compile_wgsl(&code, &name, defines)
+            }
+            ShaderSource::Glsl { vertex, fragment } => Ok(CompiledShader::Glsl {
+                vertex: Box::new(parse_glsl(vertex, ShaderStage::Vertex, defines)?),
+                fragment: Box::new(parse_glsl(fragment, ShaderStage::Fragment, defines)?),
+            }),
+            ShaderSource::SpirV(bytes) => {
+                // same options wgpu uses for its own spir-v input
+                let options = naga::front::spv::Options {
+                    adjust_coordinate_space: false,
+                    strict_capabilities: true,
+                    block_ctx_dump_prefix: None,
+                };
+                naga::front::spv::parse_u8_slice(bytes, &options)
+                    .map(CompiledShader::SpirV)
+                    .map_err(|e| ShaderCompileError::SpirV(e.to_string()))
+            }
+        }
+    }
+}
+
+fn compile_wgsl(
+    code: &str,
+    name: &str,
+    defines: &[(String, String)],
+) -> Result<CompiledShader, ShaderCompileError> {
+    let shader = preprocess(code, name, defines)?;
+    let module = shader.parse()?;
+    Ok(CompiledShader::Wgsl {
+        code: shader.code,
+        module,
+    })
+}
+
+// glsl has its own preprocessor, the defines are handed to it
+fn parse_glsl(
+    code: &str,
+    stage: ShaderStage,
+    defines: &[(String, String)],
+) -> Result<naga::Module, ShaderCompileError> {
+    let mut options = naga::front::glsl::Options::from(stage);
+    options.defines.extend(defines.iter().cloned());
+
+    naga::front::glsl::Frontend::default()
+        .parse(&options, code)
+        .map_err(|e| {
+            let error = &e.errors[0];
+            let (line, column) = error
+                .location(code)
+                .map(|e| (e.line_number as usize, e.line_position as usize))
+                .unwrap_or((1, 1));
+            ShaderCompileError::Glsl {
+                stage,
+                line,
+                column,
+                message: error.kind.to_string(),
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VERTEX: &str = "
+        #version 450
+        layout(location = 0) in vec3 position;
+        layout(set = 0, binding = 0) uniform Camera {
+            mat4 view_proj;
+        };
+
+        void main() {
+            gl_Position = view_proj * vec4(position, 1.0);
+        }
+    ";
+
+    const FRAGMENT: &str = "
+        #version 450
+        layout(location = 0) out vec4 color;
+
+        void main() {
+        #ifdef RED
+            color = vec4(1.0, 0.0, 0.0, 1.0);
+        #else
+            color = vec4(1.0);
+        #endif
+        }
+    ";
+
+    fn glsl(vertex: &str, fragment: &str) -> ShaderSource {
+        ShaderSource::Glsl {
+            vertex: vertex.to_string(),
+            fragment: fragment.to_string(),
+        }
+    }
+
+    const ENTRIES: &[(ShaderStage, &str)] = &[
+        (ShaderStage::Vertex, "main"),
+        (ShaderStage::Fragment, "main"),
+    ];
+
+    #[test]
+    fn glsl_compiles() {
+        let defines = [("RED".to_string(), String::new())];
+        let compiled = glsl(VERTEX, FRAGMENT)
+            .compile("test", ENTRIES, &defines)
+            .unwrap();
+        let CompiledShader::Glsl { vertex, fragment } = &compiled else {
+            panic!("glsl compiled to another kind of shader");
+        };
+        assert_eq!(vertex.entry_points[0].stage, ShaderStage::Vertex);
+        assert_eq!(fragment.entry_points[0].stage, ShaderStage::Fragment);
+        assert_eq!(compiled.entry_point("vs_main"), "main");
+    }
+
+    #[test]
+    fn glsl_wrong_entry_point() {
+        let entries = [
+            (ShaderStage::Vertex, "main"),
+            (ShaderStage::Fragment, "fs_main"),
+        ];
+        let err = glsl(VERTEX, FRAGMENT)
+            .compile("test", &entries, &[])
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err,
+            ShaderCompileError::GlslEntryPoint { stage: ShaderStage::Fragment, entry } if entry == "fs_main"
+        ));
+    }
+
+    #[test]
+    fn glsl_compute() {
+        let err = glsl(VERTEX, FRAGMENT)
+            .compile("test", &[(ShaderStage::Compute, "main")], &[])
+            .err()
+            .unwrap();
+        assert!(matches!(err, ShaderCompileError::GlslCompute));
+    }
+
+    #[test]
+    fn glsl_syntax_error() {
+        let broken = "#version 450\nvoid main() {\n    float a = ;\n}\n";
+        let err = glsl(broken, FRAGMENT)
+            .compile("test", ENTRIES, &[])
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err,
+            ShaderCompileError::Glsl {
+                stage: ShaderStage::Vertex,
+                line: 3,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn missing_file() {
+        let path = std::path::PathBuf::from("does/not/exist.wgsl");
+        let err = ShaderSource::File(crate::utils::FilePath::AbsolutePath(path))
+            .compile("test", &[], &[])
+            .err()
+            .unwrap();
+        assert!(
+            matches!(err, ShaderCompileError::Load { name, .. } if name == "does/not/exist.wgsl")
+        );
+    }
+}
@@ -0,0 +1,518 @@
+use glam::{Mat2, Quat, Vec3A};
+
+use crate::types::{IVec2, IVec3, IVec4, Mat3, Mat4, UVec2, UVec3, UVec4, Vec2, Vec3, Vec4};
+
+pub use jandering_engine_derive::{DataLayout, ShaderType};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldLayout {
+    pub name: String,
+    pub offset: u64,
+    pub size: u64,
+}
+
+// byte layout of a uniform or storage struct, either as rust lays it out or as the shader reads it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructLayout {
+    pub name: String,
+    pub size: u64,
+    pub fields: Vec<FieldLayout>,
+}
+
+// the layout of a #[repr(C)] struct, typed buffers check it against the wgsl struct they're bound to
+pub trait DataLayout {
+    fn data_layout() -> StructLayout;
+}
+
+#[derive(Debug, Clone)]
+pub enum DataLayoutError {
+    Size {
+        name: String,
+        size: u64,
+        expected: u64,
+    },
+    FieldOffset {
+        name: String,
+        field: String,
+        offset: u64,
+        expected: u64,
+    },
+    FieldSize {
+        name: String,
+        field: String,
+        size: u64,
+        expected: u64,
+    },
+}
+
+impl std::fmt::Display for DataLayoutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataLayoutError::Size {
+                name,
+                size,
+                expected,
+            } => write!(
+                f,
+                "`{name}` is {size} bytes but the shader expects {expected}"
+            ),
+            DataLayoutError::FieldOffset {
+                name,
+                field,
+                offset,
+                expected,
+            } => write!(
+                f,
+                "`{name}.{field}` is at offset {offset} but the shader reads it at {expected}, padding is probably missing before it"
+            ),
+            DataLayoutError::FieldSize {
+                name,
+                field,
+                size,
+                expected,
+            } => write!(
+                f,
+                "`{name}.{field}` is {size} bytes but the shader reads {expected}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DataLayoutError {}
+
+impl StructLayout {
+    // fields are matched by name, the ones only one side has are skipped. elements of an array
+    // have to be exactly as big as the array stride, a single struct can be bigger
+    pub fn check(&self, shader: &StructLayout, array: bool) -> Result<(), DataLayoutError> {
+        if self.size < shader.size || (array && self.size != shader.size) {
+            return Err(DataLayoutError::Size {
+                name: self.name.clone(),
+                size: self.size,
+                expected: shader.size,
+            });
+        }
+
+        for expected in shader.fields.iter() {
+            let Some(field) = self.fields.iter().find(|e| e.name == expected.name) else {
+                continue;
+            };
+
+            if field.offset != expected.offset {
+                return Err(DataLayoutError::FieldOffset {
+                    name: self.name.clone(),
+                    field: field.name.clone(),
+                    offset: field.offset,
+                    expected: expected.offset,
+                });
+            }
+
+            // a Vec3A in place of a vec3 is fine, it just carries its padding along
+            if field.size < expected.size {
+                return Err(DataLayoutError::FieldSize {
+                    name: self.name.clone(),
+                    field: field.name.clone(),
+                    size: field.size,
+                    expected: expected.size,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// std140 follows wgsl's uniform buffer rules, std430 its storage buffer rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutRules {
+    Std140,
+    Std430,
+}
+
+impl LayoutRules {
+    pub fn struct_align(self, align: u64) -> u64 {
+        match self {
+            LayoutRules::Std140 => round_up(align, 16),
+            LayoutRules::Std430 => align,
+        }
+    }
+
+    pub fn array_stride(self, align: u64, size: u64) -> u64 {
+        let stride = round_up(size, align);
+        match self {
+            LayoutRules::Std140 => round_up(stride, 16),
+            LayoutRules::Std430 => stride,
+        }
+    }
+}
+
+pub const fn round_up(value: u64, align: u64) -> u64 {
+    (value + align - 1) / align * align
+}
+
+// writes values with the padding wgsl expects, so structs don't need hand written padding fields
+pub trait ShaderType {
+    fn align(rules: LayoutRules) -> u64;
+    fn size(rules: LayoutRules) -> u64;
+    // `bytes` starts where the value goes and is at least `size(rules)` long
+    fn write(&self, rules: LayoutRules, bytes: &mut [u8]);
+
+    // where `write` puts the fields, #[derive(ShaderType)] fills them in for typed buffers to check
+    fn shader_layout(rules: LayoutRules) -> StructLayout
+    where
+        Self: Sized,
+    {
+        StructLayout {
+            name: std::any::type_name::<Self>().to_string(),
+            size: Self::size(rules),
+            fields: Vec::new(),
+        }
+    }
+
+    fn std140_bytes(&self) -> Vec<u8>
+    where
+        Self: Sized,
+    {
+        let mut bytes = vec![0; Self::size(LayoutRules::Std140) as usize];
+        self.write(LayoutRules::Std140, &mut bytes);
+        bytes
+    }
+
+    fn std430_bytes(&self) -> Vec<u8>
+    where
+        Self: Sized,
+    {
+        let mut bytes = vec![0; Self::size(LayoutRules::Std430) as usize];
+        self.write(LayoutRules::Std430, &mut bytes);
+        bytes
+    }
+}
+
+macro_rules! impl_shader_type {
+    ($($ty:ty => $align:expr, $size:expr;)*) => {
+        $(
+            impl ShaderType for $ty {
+                fn align(_: LayoutRules) -> u64 {
+                    $align
+                }
+
+                fn size(_: LayoutRules) -> u64 {
+                    $size
+                }
+
+                fn write(&self, _: LayoutRules, bytes: &mut [u8]) {
+                    bytes[..$size].copy_from_slice(&bytemuck::bytes_of(self)[..$size]);
+                }
+            }
+        )*
+    };
+}
+
+impl_shader_type! {
+    f32 => 4, 4;
+    u32 => 4, 4;
+    i32 => 4, 4;
+    Vec2 => 8, 8;
+    UVec2 => 8, 8;
+    IVec2 => 8, 8;
+    Vec3 => 16, 12;
+    UVec3 => 16, 12;
+    IVec3 => 16, 12;
+    Vec4 => 16, 16;
+    UVec4 => 16, 16;
+    IVec4 => 16, 16;
+    Quat => 16, 16;
+    Mat4 => 16, 64;
+}
+
+impl ShaderType for Vec3A {
+    fn align(_: LayoutRules) -> u64 {
+        16
+    }
+
+    fn size(_: LayoutRules) -> u64 {
+        12
+    }
+
+    fn write(&self, rules: LayoutRules, bytes: &mut [u8]) {
+        Vec3::from(*self).write(rules, bytes);
+    }
+}
+
+impl ShaderType for Mat2 {
+    fn align(_: LayoutRules) -> u64 {
+        8
+    }
+
+    fn size(_: LayoutRules) -> u64 {
+        16
+    }
+
+    fn write(&self, _: LayoutRules, bytes: &mut [u8]) {
+        bytes[..16].copy_from_slice(bytemuck::bytes_of(&self.to_cols_array()));
+    }
+}
+
+// every column is a vec3 padded to 16 bytes
+impl ShaderType for Mat3 {
+    fn align(_: LayoutRules) -> u64 {
+        16
+    }
+
+    fn size(_: LayoutRules) -> u64 {
+        48
+    }
+
+    fn write(&self, _: LayoutRules, bytes: &mut [u8]) {
+        for (i, column) in [self.x_axis, self.y_axis, self.z_axis].iter().enumerate() {
+            column.write(LayoutRules::Std430, &mut bytes[i * 16..]);
+        }
+    }
+}
+
+impl<T: ShaderType, const N: usize> ShaderType for [T; N] {
+    fn align(rules: LayoutRules) -> u64 {
+        rules.struct_align(T::align(rules))
+    }
+
+    fn size(rules: LayoutRules) -> u64 {
+        N as u64 * rules.array_stride(T::align(rules), T::size(rules))
+    }
+
+    fn write(&self, rules: LayoutRules, bytes: &mut [u8]) {
+        let stride = rules.array_stride(T::align(rules), T::size(rules)) as usize;
+        for (i, element) in self.iter().enumerate() {
+            element.write(rules, &mut bytes[i * stride..]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shader::reflection::ShaderReflection;
+
+    #[derive(ShaderType)]
+    struct Light {
+        position: Vec3,
+        intensity: f32,
+        color: Vec3,
+    }
+
+    #[derive(ShaderType)]
+    struct Inner {
+        value: f32,
+    }
+
+    #[derive(ShaderType)]
+    struct Outer {
+        first: f32,
+        inner: Inner,
+        weights: [f32; 3],
+        last: f32,
+    }
+
+    fn field(name: &str, offset: u64, size: u64) -> FieldLayout {
+        FieldLayout {
+            name: name.to_string(),
+            offset,
+            size,
+        }
+    }
+
+    fn layout(name: &str, size: u64, fields: Vec<FieldLayout>) -> StructLayout {
+        StructLayout {
+            name: name.to_string(),
+            size,
+            fields,
+        }
+    }
+
+    // the layout naga gives the struct `name` bound at @group(0) @binding(0)
+    fn reflect(code: &str) -> StructLayout {
+        let reflection = ShaderReflection::from_wgsl(code, &["main"]).unwrap();
+        reflection.data_layout(0, 0).unwrap().0.clone()
+    }
+
+    #[test]
+    fn vec3_padding() {
+        // a scalar fits in the vec3's padding, another vec3 doesn't
+        let expected = layout(
+            "Light",
+            32,
+            vec![
+                field("position", 0, 12),
+                field("intensity", 12, 4),
+                field("color", 16, 12),
+            ],
+        );
+        assert_eq!(Light::shader_layout(LayoutRules::Std140), expected);
+        assert_eq!(Light::shader_layout(LayoutRules::Std430), expected);
+    }
+
+    #[test]
+    fn array_strides() {
+        assert_eq!(<[f32; 3]>::size(LayoutRules::Std140), 48);
+        assert_eq!(<[f32; 3]>::size(LayoutRules::Std430), 12);
+        assert_eq!(<[Vec3; 2]>::size(LayoutRules::Std140), 32);
+        assert_eq!(<[Vec3; 2]>::size(LayoutRules::Std430), 32);
+        assert_eq!(<[Vec2; 2]>::size(LayoutRules::Std140), 32);
+        assert_eq!(<[Vec2; 2]>::size(LayoutRules::Std430), 16);
+
+        let mut bytes = vec![0; 48];
+        [1.0f32, 2.0, 3.0].write(LayoutRules::Std140, &mut bytes);
+        let floats: &[f32] = bytemuck::cast_slice(&bytes);
+        assert_eq!(floats[0], 1.0);
+        assert_eq!(floats[4], 2.0);
+        assert_eq!(floats[8], 3.0);
+    }
+
+    #[test]
+    fn nested_structs() {
+        assert_eq!(
+            Outer::shader_layout(LayoutRules::Std140),
+            layout(
+                "Outer",
+                96,
+                vec![
+                    field("first", 0, 4),
+                    field("inner", 16, 16),
+                    field("weights", 32, 48),
+                    field("last", 80, 4),
+                ],
+            )
+        );
+        assert_eq!(
+            Outer::shader_layout(LayoutRules::Std430),
+            layout(
+                "Outer",
+                24,
+                vec![
+                    field("first", 0, 4),
+                    field("inner", 4, 4),
+                    field("weights", 8, 12),
+                    field("last", 20, 4),
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn std140_bytes() {
+        let outer = Outer {
+            first: 1.0,
+            inner: Inner { value: 2.0 },
+            weights: [3.0, 4.0, 5.0],
+            last: 6.0,
+        };
+        let bytes = outer.std140_bytes();
+        assert_eq!(bytes.len(), 96);
+        let floats: &[f32] = bytemuck::cast_slice(&bytes);
+        for (index, value) in [
+            (0, 1.0),
+            (4, 2.0),
+            (8, 3.0),
+            (12, 4.0),
+            (16, 5.0),
+            (20, 6.0),
+        ] {
+            assert_eq!(floats[index], value);
+        }
+    }
+
+    #[test]
+    fn matches_naga() {
+        let uniform = reflect(
+            "
+            struct Inner { value: f32 }
+            struct Outer { first: f32, @align(16) inner: Inner, weights: array<vec4<f32>, 3>, last: f32 }
+            @group(0) @binding(0) var<uniform> data: Outer;
+            @compute @workgroup_size(1)
+            fn main() { let a = data; }
+            ",
+        );
+        // wgsl doesn't pad uniforms itself, it rejects the structs std140 would pad differently
+        assert_eq!(uniform.fields[0], field("first", 0, 4));
+        assert_eq!(uniform.fields[1], field("inner", 16, 4));
+        assert_eq!(uniform.fields[2], field("weights", 32, 48));
+        assert_eq!(uniform.fields[3], field("last", 80, 4));
+        Outer::shader_layout(LayoutRules::Std140)
+            .check(&uniform, false)
+            .unwrap();
+
+        let storage = reflect(
+            "
+            struct Inner { value: f32 }
+            struct Outer { first: f32, inner: Inner, weights: array<f32, 3>, last: f32 }
+            @group(0) @binding(0) var<storage, read> data: array<Outer>;
+            @compute @workgroup_size(1)
+            fn main() { let a = data[0]; }
+            ",
+        );
+        Outer::shader_layout(LayoutRules::Std430)
+            .check(&storage, true)
+            .unwrap();
+
+        let light = reflect(
+            "
+            struct Light { position: vec3<f32>, intensity: f32, color: vec3<f32> }
+            @group(0) @binding(0) var<uniform> data: Light;
+            @compute @workgroup_size(1)
+            fn main() { let a = data; }
+            ",
+        );
+        Light::shader_layout(LayoutRules::Std140)
+            .check(&light, false)
+            .unwrap();
+    }
+
+    #[test]
+    fn check_errors() {
+        let shader = layout(
+            "Light",
+            32,
+            vec![field("position", 0, 12), field("color", 16, 12)],
+        );
+
+        let small = layout("Light", 28, vec![field("position", 0, 12)]);
+        assert!(matches!(
+            small.check(&shader, false),
+            Err(DataLayoutError::Size {
+                size: 28,
+                expected: 32,
+                ..
+            })
+        ));
+
+        // a single struct can be bigger, an array element can't
+        let big = layout("Light", 48, vec![field("position", 0, 12)]);
+        big.check(&shader, false).unwrap();
+        assert!(matches!(
+            big.check(&shader, true),
+            Err(DataLayoutError::Size {
+                size: 48,
+                expected: 32,
+                ..
+            })
+        ));
+
+        let unpadded = layout(
+            "Light",
+            32,
+            vec![field("position", 0, 12), field("color", 12, 12)],
+        );
+        assert!(matches!(
+            unpadded.check(&shader, false),
+            Err(DataLayoutError::FieldOffset { field, offset: 12, expected: 16, .. }) if field == "color"
+        ));
+
+        let narrow = layout(
+            "Light",
+            32,
+            vec![field("position", 0, 8), field("color", 16, 12)],
+        );
+        assert!(matches!(
+            narrow.check(&shader, false),
+            Err(DataLayoutError::FieldSize { field, size: 8, expected: 12, .. }) if field == "position"
+        ));
+    }
+}
@@ -1,19 +1,42 @@
-use crate::{bind_group::BindGroupLayoutDescriptor, texture::TextureFormat, utils::FilePath};
+use std::borrow::Cow;
+
+use crate::{
+    bind_group::BindGroupLayoutDescriptor, renderer::ShaderVariant, texture::TextureFormat,
+    utils::FilePath,
+};
+
+pub use jandering_engine_derive::VertexLayout;
+
+pub mod frontend;
+pub mod layout;
+pub mod reflection;
+
+// the preprocessor lives in its own crate so build scripts can use it without the engine
+#[cfg(not(target_arch = "wasm32"))]
+pub use je_shader_build as build;
+pub use je_shader_build::preprocessor;
 
 #[derive(Clone)]
 pub enum ShaderSource {
     Code(String),
     #[cfg(not(target_arch = "wasm32"))]
     File(FilePath),
+    // each stage is parsed on its own, both use `main` as the entry point so vs_entry and
+    // fs_entry have to be "main" too. only for render shaders
+    Glsl {
+        vertex: String,
+        fragment: String,
+    },
+    SpirV(Vec<u8>),
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum BufferLayoutStepMode {
     Vertex,
     Instance,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum BufferLayoutEntryDataType {
     Float32,
     Float32x2,
@@ -21,72 +44,216 @@ pub enum BufferLayoutEntryDataType {
     Float32x4,
 
     U32,
+    Uint32x2,
+    Uint32x3,
+    Uint32x4,
+
+    Sint32,
+    Sint32x2,
+    Sint32x3,
+    Sint32x4,
+
+    Uint8x2,
+    Uint8x4,
+    Sint8x2,
+    Sint8x4,
+    Unorm8x2,
+    Unorm8x4,
+    Snorm8x2,
+    Snorm8x4,
+
+    Uint16x2,
+    Uint16x4,
+    Sint16x2,
+    Sint16x4,
+    Unorm16x2,
+    Unorm16x4,
+    Snorm16x2,
+    Snorm16x4,
+    Float16x2,
+    Float16x4,
+
+    Unorm10_10_10_2,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct BufferLayoutEntry {
     pub location: u32,
     pub data_type: BufferLayoutEntryDataType,
+    // None places the entry right after the previous one
+    pub offset: Option<u64>,
 }
 
 impl BufferLayoutEntryDataType {
     pub fn size_bytes(&self) -> u64 {
         match self {
-            BufferLayoutEntryDataType::Float32 | BufferLayoutEntryDataType::U32 => 4,
-            BufferLayoutEntryDataType::Float32x2 => 8,
-            BufferLayoutEntryDataType::Float32x3 => 12,
-            BufferLayoutEntryDataType::Float32x4 => 16,
+            BufferLayoutEntryDataType::Uint8x2
+            | BufferLayoutEntryDataType::Sint8x2
+            | BufferLayoutEntryDataType::Unorm8x2
+            | BufferLayoutEntryDataType::Snorm8x2 => 2,
+            BufferLayoutEntryDataType::Float32
+            | BufferLayoutEntryDataType::U32
+            | BufferLayoutEntryDataType::Sint32
+            | BufferLayoutEntryDataType::Uint8x4
+            | BufferLayoutEntryDataType::Sint8x4
+            | BufferLayoutEntryDataType::Unorm8x4
+            | BufferLayoutEntryDataType::Snorm8x4
+            | BufferLayoutEntryDataType::Uint16x2
+            | BufferLayoutEntryDataType::Sint16x2
+            | BufferLayoutEntryDataType::Unorm16x2
+            | BufferLayoutEntryDataType::Snorm16x2
+            | BufferLayoutEntryDataType::Float16x2
+            | BufferLayoutEntryDataType::Unorm10_10_10_2 => 4,
+            BufferLayoutEntryDataType::Float32x2
+            | BufferLayoutEntryDataType::Uint32x2
+            | BufferLayoutEntryDataType::Sint32x2
+            | BufferLayoutEntryDataType::Uint16x4
+            | BufferLayoutEntryDataType::Sint16x4
+            | BufferLayoutEntryDataType::Unorm16x4
+            | BufferLayoutEntryDataType::Snorm16x4
+            | BufferLayoutEntryDataType::Float16x4 => 8,
+            BufferLayoutEntryDataType::Float32x3
+            | BufferLayoutEntryDataType::Uint32x3
+            | BufferLayoutEntryDataType::Sint32x3 => 12,
+            BufferLayoutEntryDataType::Float32x4
+            | BufferLayoutEntryDataType::Uint32x4
+            | BufferLayoutEntryDataType::Sint32x4 => 16,
         }
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct BufferLayout {
     pub step_mode: BufferLayoutStepMode,
-    pub entries: &'static [BufferLayoutEntry],
+    pub entries: Vec<BufferLayoutEntry>,
+    // None uses the end of the furthest entry, set it for padded structs
+    pub stride: Option<u64>,
+}
+
+pub trait VertexLayout {
+    fn desc() -> BufferLayout;
+}
+
+// used by #[derive(VertexLayout)] and #[derive(DataLayout)], align has to be a power of two
+#[doc(hidden)]
+pub const fn repr_c_offset(end: usize, align: usize) -> usize {
+    (end + align - 1) & !(align - 1)
+}
+
+// vertex buffer strides have to be a multiple of this
+pub const VERTEX_STRIDE_ALIGNMENT: u64 = 4;
+
+impl BufferLayout {
+    pub fn stride(&self) -> u64 {
+        if let Some(stride) = self.stride {
+            assert!(
+                stride % VERTEX_STRIDE_ALIGNMENT == 0,
+                "vertex buffer stride {stride} isn't a multiple of {VERTEX_STRIDE_ALIGNMENT}"
+            );
+            return stride;
+        }
+
+        let mut offset = 0;
+        let mut end = 0;
+        for entry in self.entries.iter() {
+            let entry_offset = entry.offset.unwrap_or(offset);
+            offset = entry_offset + entry.data_type.size_bytes();
+            end = end.max(offset);
+        }
+        repr_c_offset(end as usize, VERTEX_STRIDE_ALIGNMENT as usize) as u64
+    }
 }
 
 #[derive(Clone)]
 pub struct ShaderDescriptor {
-    pub name: &'static str,
+    pub name: Cow<'static, str>,
     pub source: ShaderSource,
     pub descriptors: Vec<BufferLayout>,
     pub bind_group_layout_descriptors: Vec<BindGroupLayoutDescriptor>,
-    pub vs_entry: &'static str,
-    pub fs_entry: &'static str,
+    pub vs_entry: Cow<'static, str>,
+    pub fs_entry: Cow<'static, str>,
     pub backface_culling: bool,
     pub depth: bool,
     pub stripped: bool,
+    // ignored, windows set their sample count with SurfaceConfig::msaa_samples and passes into
+    // textures use the texture's
+    #[deprecated(note = "ignored, use SurfaceConfig::msaa_samples for windows")]
     pub multisample: u32,
     pub target_texture_format: Option<TextureFormat>,
+    // name and value pairs for #ifdef and substitution, the value can be empty
+    pub defines: Vec<(String, String)>,
+    // keys that can be toggled per variant, each one is defined for the variants that enable it
+    pub features: Vec<Cow<'static, str>>,
+    // fill empty layouts in from the shader source. given layouts are checked against it
+    // when this is set or EngineConfig::validate_passes is on
+    pub reflect: bool,
+    // vertex entry point arguments whose buffers step per instance when descriptors are reflected
+    pub instance_arguments: Vec<Cow<'static, str>>,
 }
 
 #[derive(Clone)]
 pub struct ComputeShaderDescriptor {
     pub source: ShaderSource,
     pub bind_group_layout_descriptors: Vec<BindGroupLayoutDescriptor>,
-    pub entry: &'static str,
+    pub entry: Cow<'static, str>,
+    pub defines: Vec<(String, String)>,
+    pub reflect: bool,
 }
 
+#[allow(deprecated)]
 impl Default for ShaderDescriptor {
     fn default() -> Self {
         Self {
-            name: "Unnamed Shader",
+            name: "Unnamed Shader".into(),
             source: ShaderSource::Code(include_str!("default_shader.wgsl").to_string()),
             descriptors: Vec::new(),
             bind_group_layout_descriptors: Vec::new(),
-            vs_entry: "vs_main",
-            fs_entry: "fs_main",
+            vs_entry: "vs_main".into(),
+            fs_entry: "fs_main".into(),
             backface_culling: true,
             depth: false,
             stripped: false,
             multisample: 1,
             target_texture_format: Some(TextureFormat::Bgra8U),
+            defines: Vec::new(),
+            features: Vec::new(),
+            reflect: false,
+            instance_arguments: Vec::new(),
         }
     }
 }
 
 impl ShaderDescriptor {
+    pub fn variant(&self, features: &[&str]) -> Option<ShaderVariant> {
+        let mut variant = 0;
+        for feature in features {
+            let index = self.features.iter().position(|e| e == feature)?;
+            variant |= 1 << index;
+        }
+        Some(ShaderVariant(variant))
+    }
+
+    // names of the features the variant enables
+    pub(crate) fn variant_features(&self, variant: ShaderVariant) -> Vec<&str> {
+        self.features
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| variant.0 & (1 << i) != 0)
+            .map(|(_, e)| e.as_ref())
+            .collect()
+    }
+
+    // the descriptor's defines plus the features the variant enables
+    pub(crate) fn variant_defines(&self, variant: ShaderVariant) -> Vec<(String, String)> {
+        let mut defines = self.defines.clone();
+        for (i, feature) in self.features.iter().enumerate() {
+            if variant.0 & (1 << i) != 0 {
+                defines.push((feature.to_string(), String::new()));
+            }
+        }
+        defines
+    }
+
     pub fn flat() -> Self {
         Self {
             source: ShaderSource::Code(include_str!("flat_shader.wgsl").to_string()),
@@ -94,3 +261,163 @@ impl ShaderDescriptor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::mem::{offset_of, size_of};
+
+    use super::*;
+    use crate::types::{Mat4, Vec2};
+
+    fn entry(
+        location: u32,
+        data_type: BufferLayoutEntryDataType,
+        offset: usize,
+    ) -> BufferLayoutEntry {
+        BufferLayoutEntry {
+            location,
+            data_type,
+            offset: Some(offset as u64),
+        }
+    }
+
+    #[repr(C)]
+    #[derive(VertexLayout)]
+    struct Padded {
+        a: [u8; 2],
+        // starts 2 bytes after the end of `a`
+        b: u32,
+        c: [u16; 2],
+        d: Vec2,
+    }
+
+    #[test]
+    fn offsets_follow_repr_c_padding() {
+        use BufferLayoutEntryDataType::*;
+
+        let layout = Padded::desc();
+        assert_eq!(layout.step_mode, BufferLayoutStepMode::Vertex);
+        assert_eq!(
+            layout.entries,
+            [
+                entry(0, Uint8x2, offset_of!(Padded, a)),
+                entry(1, U32, offset_of!(Padded, b)),
+                entry(2, Uint16x2, offset_of!(Padded, c)),
+                entry(3, Float32x2, offset_of!(Padded, d)),
+            ]
+        );
+        assert_eq!(offset_of!(Padded, b), 4);
+        assert_eq!(layout.stride(), size_of::<Padded>() as u64);
+    }
+
+    #[repr(C)]
+    #[derive(VertexLayout)]
+    #[layout(step = instance, start_location = 5)]
+    struct Instance {
+        model: Mat4,
+        #[layout(skip)]
+        _id: u32,
+        #[layout(format = Unorm8x4)]
+        color: [u8; 4],
+    }
+
+    #[test]
+    fn matrices_take_a_location_per_column() {
+        use BufferLayoutEntryDataType::*;
+
+        let layout = Instance::desc();
+        assert_eq!(layout.step_mode, BufferLayoutStepMode::Instance);
+        assert_eq!(
+            layout.entries,
+            [
+                entry(5, Float32x4, 0),
+                entry(6, Float32x4, 16),
+                entry(7, Float32x4, 32),
+                entry(8, Float32x4, 48),
+                entry(9, Unorm8x4, offset_of!(Instance, color)),
+            ]
+        );
+        assert_eq!(layout.stride(), size_of::<Instance>() as u64);
+    }
+
+    #[test]
+    fn computed_strides_are_aligned() {
+        let layout = BufferLayout {
+            step_mode: BufferLayoutStepMode::Vertex,
+            entries: vec![
+                BufferLayoutEntry {
+                    location: 0,
+                    data_type: BufferLayoutEntryDataType::Float32,
+                    offset: None,
+                },
+                BufferLayoutEntry {
+                    location: 1,
+                    data_type: BufferLayoutEntryDataType::Uint8x2,
+                    offset: None,
+                },
+            ],
+            stride: None,
+        };
+        assert_eq!(layout.stride(), 8);
+    }
+
+    #[test]
+    #[should_panic(expected = "isn't a multiple of 4")]
+    fn unaligned_strides_are_rejected() {
+        BufferLayout {
+            step_mode: BufferLayoutStepMode::Vertex,
+            entries: Vec::new(),
+            stride: Some(6),
+        }
+        .stride();
+    }
+
+    fn features() -> ShaderDescriptor {
+        ShaderDescriptor {
+            defines: vec![("COUNT".to_string(), "4".to_string())],
+            features: vec!["SHADOWS".into(), "FOG".into(), "SKINNED".into()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn variant_bits_follow_feature_order() {
+        let desc = features();
+        assert_eq!(desc.variant(&[]), Some(ShaderVariant(0)));
+        assert_eq!(desc.variant(&["SHADOWS"]), Some(ShaderVariant(0b001)));
+        assert_eq!(
+            desc.variant(&["SKINNED", "FOG"]),
+            Some(ShaderVariant(0b110))
+        );
+        assert_eq!(desc.variant(&["FOG", "FOG"]), Some(ShaderVariant(0b010)));
+        assert_eq!(desc.variant(&["FOG", "BLOOM"]), None);
+    }
+
+    #[test]
+    fn variant_features_and_defines() {
+        let desc = features();
+        let variant = desc.variant(&["SKINNED", "SHADOWS"]).unwrap();
+        assert_eq!(desc.variant_features(variant), vec!["SHADOWS", "SKINNED"]);
+        assert_eq!(
+            desc.variant_defines(variant),
+            vec![
+                ("COUNT".to_string(), "4".to_string()),
+                ("SHADOWS".to_string(), String::new()),
+                ("SKINNED".to_string(), String::new()),
+            ]
+        );
+
+        // features match by name, so a reordered descriptor maps to the same ones
+        let mut reordered = features();
+        reordered.features.reverse();
+        let remapped = reordered.variant(&desc.variant_features(variant)).unwrap();
+        assert_eq!(remapped, ShaderVariant(0b101));
+        assert_eq!(
+            reordered.variant_features(remapped),
+            vec!["SKINNED", "SHADOWS"]
+        );
+
+        assert!(desc.variant_features(ShaderVariant(0)).is_empty());
+        assert_eq!(desc.variant_defines(ShaderVariant(0)), desc.defines);
+    }
+}
@@ -0,0 +1,1113 @@
+use std::collections::HashSet;
+
+use naga::{AddressSpace, Binding, Handle, ImageClass, ScalarKind, ShaderStage, TypeInner};
+
+use crate::bind_group::{
+    BindGroupLayoutDescriptor, BindGroupLayoutDescriptorEntry, SamplerType, TextureSampleType,
+};
+
+use super::{
+    frontend::CompiledShader,
+    layout::{FieldLayout, StructLayout},
+    BufferLayout, BufferLayoutEntry, BufferLayoutEntryDataType, BufferLayoutStepMode,
+    ComputeShaderDescriptor, ShaderDescriptor,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ShaderBindingType {
+    Uniform,
+    Storage { read_only: bool },
+    // float textures reflect as Filterable, the shader can't tell whether they're filtered
+    Texture { sample_type: TextureSampleType },
+    Sampler { comparison: bool },
+}
+
+impl std::fmt::Display for ShaderBindingType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShaderBindingType::Uniform => write!(f, "uniform buffer"),
+            ShaderBindingType::Storage { read_only: true } => write!(f, "read only storage buffer"),
+            ShaderBindingType::Storage { read_only: false } => write!(f, "storage buffer"),
+            ShaderBindingType::Texture { sample_type } => {
+                write!(f, "{}", describe_texture(*sample_type))
+            }
+            ShaderBindingType::Sampler { comparison: true } => write!(f, "comparison sampler"),
+            ShaderBindingType::Sampler { comparison: false } => write!(f, "sampler"),
+        }
+    }
+}
+
+impl ShaderBindingType {
+    fn descriptor_entry(self) -> BindGroupLayoutDescriptorEntry {
+        match self {
+            ShaderBindingType::Uniform => BindGroupLayoutDescriptorEntry::Data {
+                is_uniform: true,
+                read_only: false,
+            },
+            ShaderBindingType::Storage { read_only } => BindGroupLayoutDescriptorEntry::Data {
+                is_uniform: false,
+                read_only,
+            },
+            ShaderBindingType::Texture { sample_type } => {
+                BindGroupLayoutDescriptorEntry::Texture { sample_type }
+            }
+            ShaderBindingType::Sampler { comparison } => BindGroupLayoutDescriptorEntry::Sampler {
+                sampler_type: if comparison {
+                    SamplerType::Comparison
+                } else {
+                    SamplerType::Filtering
+                },
+            },
+        }
+    }
+
+    // same compatibility rules wgpu applies when creating the pipeline
+    fn accepts(self, entry: &BindGroupLayoutDescriptorEntry) -> bool {
+        match (self, entry) {
+            (
+                ShaderBindingType::Uniform,
+                BindGroupLayoutDescriptorEntry::Data { is_uniform, .. },
+            ) => *is_uniform,
+            (
+                ShaderBindingType::Storage { read_only },
+                BindGroupLayoutDescriptorEntry::Data {
+                    is_uniform,
+                    read_only: layout_read_only,
+                },
+            ) => !is_uniform && (read_only || !layout_read_only),
+            (
+                ShaderBindingType::Texture {
+                    sample_type: TextureSampleType::Filterable,
+                },
+                BindGroupLayoutDescriptorEntry::Texture { sample_type },
+            ) => matches!(
+                sample_type,
+                TextureSampleType::Filterable | TextureSampleType::NonFilterable
+            ),
+            (
+                ShaderBindingType::Texture { sample_type },
+                BindGroupLayoutDescriptorEntry::Texture {
+                    sample_type: layout_sample_type,
+                },
+            ) => sample_type == *layout_sample_type,
+            (
+                ShaderBindingType::Sampler { comparison },
+                BindGroupLayoutDescriptorEntry::Sampler { sampler_type },
+            ) => comparison == matches!(sampler_type, SamplerType::Comparison),
+            _ => false,
+        }
+    }
+}
+
+pub(crate) fn describe_entry(entry: &BindGroupLayoutDescriptorEntry) -> &'static str {
+    match entry {
+        BindGroupLayoutDescriptorEntry::Data {
+            is_uniform: true, ..
+        } => "uniform buffer",
+        BindGroupLayoutDescriptorEntry::Data {
+            read_only: true, ..
+        } => "read only storage buffer",
+        BindGroupLayoutDescriptorEntry::Data { .. } => "storage buffer",
+        BindGroupLayoutDescriptorEntry::Texture { sample_type } => describe_texture(*sample_type),
+        BindGroupLayoutDescriptorEntry::Sampler {
+            sampler_type: SamplerType::Comparison,
+        } => "comparison sampler",
+        BindGroupLayoutDescriptorEntry::Sampler { .. } => "sampler",
+    }
+}
+
+fn describe_texture(sample_type: TextureSampleType) -> &'static str {
+    match sample_type {
+        TextureSampleType::Filterable | TextureSampleType::NonFilterable => "texture",
+        TextureSampleType::Depth => "depth texture",
+        TextureSampleType::Uint => "uint texture",
+        TextureSampleType::Sint => "sint texture",
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ShaderBinding {
+    group: u32,
+    binding: u32,
+    name: String,
+    ty: ShaderBindingType,
+    // the struct behind a uniform or storage binding, and whether it's an array of them
+    data: Option<(StructLayout, bool)>,
+}
+
+#[derive(Debug, Clone)]
+struct VertexInput {
+    location: u32,
+    name: String,
+    kind: ScalarKind,
+    components: u32,
+}
+
+#[derive(Debug, Clone)]
+struct VertexArgument {
+    // None for the loose @location arguments
+    name: Option<String>,
+    inputs: Vec<VertexInput>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ShaderLayoutError {
+    Parse(String),
+    MissingEntryPoint {
+        entry: String,
+    },
+    UnsupportedBinding {
+        group: u32,
+        binding: u32,
+        name: String,
+    },
+    // engine bind groups number their bindings by position, so there can be no holes
+    SparseBindings {
+        group: u32,
+        binding: u32,
+        name: String,
+    },
+    MissingGroup {
+        group: u32,
+        name: String,
+        given: usize,
+    },
+    MissingBinding {
+        group: u32,
+        binding: u32,
+        name: String,
+        given: usize,
+    },
+    BindingMismatch {
+        group: u32,
+        binding: u32,
+        name: String,
+        expected: String,
+        given: String,
+    },
+    UnsupportedVertexInput {
+        location: u32,
+        name: String,
+    },
+    MissingVertexInput {
+        location: u32,
+        name: String,
+    },
+    VertexInputMismatch {
+        location: u32,
+        name: String,
+        expected: String,
+        given: String,
+    },
+    MissingInstanceArgument {
+        name: String,
+    },
+}
+
+impl std::fmt::Display for ShaderLayoutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShaderLayoutError::Parse(error) => write!(f, "failed to parse shader: {error}"),
+            ShaderLayoutError::MissingEntryPoint { entry } => {
+                write!(f, "shader has no entry point named `{entry}`")
+            }
+            ShaderLayoutError::UnsupportedBinding {
+                group,
+                binding,
+                name,
+            } => write!(
+                f,
+                "@group({group}) @binding({binding}) `{name}` has a type bind groups can't hold"
+            ),
+            ShaderLayoutError::SparseBindings {
+                group,
+                binding,
+                name,
+            } => write!(
+                f,
+                "@group({group}) @binding({binding}) `{name}` leaves a gap, bindings have to be numbered 0, 1, 2, ..."
+            ),
+            ShaderLayoutError::MissingGroup { group, name, given } => write!(
+                f,
+                "@group({group}) is used by `{name}` but only {given} bind group layouts were given"
+            ),
+            ShaderLayoutError::MissingBinding {
+                group,
+                binding,
+                name,
+                given,
+            } => write!(
+                f,
+                "@group({group}) @binding({binding}) `{name}` is used but the layout for group {group} only has {given} entries"
+            ),
+            ShaderLayoutError::BindingMismatch {
+                group,
+                binding,
+                name,
+                expected,
+                given,
+            } => write!(
+                f,
+                "@group({group}) @binding({binding}) `{name}` is a {expected} in the shader but the layout has a {given}"
+            ),
+            ShaderLayoutError::UnsupportedVertexInput { location, name } => write!(
+                f,
+                "vertex input @location({location}) `{name}` has a type vertex buffers can't provide"
+            ),
+            ShaderLayoutError::MissingVertexInput { location, name } => write!(
+                f,
+                "vertex input @location({location}) `{name}` is not provided by any of the buffer layouts"
+            ),
+            ShaderLayoutError::VertexInputMismatch {
+                location,
+                name,
+                expected,
+                given,
+            } => write!(
+                f,
+                "vertex input @location({location}) `{name}` is a {expected} in the shader but the buffer layout provides {given}"
+            ),
+            ShaderLayoutError::MissingInstanceArgument { name } => write!(
+                f,
+                "`{name}` is listed as an instance argument but the vertex entry point has no struct argument with that name"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ShaderLayoutError {}
+
+// what a shader expects to be bound, read from the entry points it will be used with
+#[derive(Debug, Clone)]
+pub struct ShaderReflection {
+    bindings: Vec<ShaderBinding>,
+    vertex_arguments: Vec<VertexArgument>,
+}
+
+impl ShaderReflection {
+    pub fn from_wgsl(code: &str, entries: &[&str]) -> Result<Self, ShaderLayoutError> {
+        let module = naga::front::wgsl::parse_str(code)
+            .map_err(|e| ShaderLayoutError::Parse(e.emit_to_string(code)))?;
+        Self::from_module(&module, entries)
+    }
+
+    pub fn from_module(module: &naga::Module, entries: &[&str]) -> Result<Self, ShaderLayoutError> {
+        let stages = entries.iter().map(|e| (module, *e)).collect::<Vec<_>>();
+        Self::from_stages(&stages)
+    }
+
+    // entry points can come from separate modules, like glsl's one module per stage
+    pub fn from_stages(stages: &[(&naga::Module, &str)]) -> Result<Self, ShaderLayoutError> {
+        let mut bindings: Vec<ShaderBinding> = Vec::new();
+        let mut vertex_arguments = Vec::new();
+        for (module, entry) in stages {
+            let entry_point = module
+                .entry_points
+                .iter()
+                .find(|e| e.name == *entry)
+                .ok_or_else(|| ShaderLayoutError::MissingEntryPoint {
+                    entry: entry.to_string(),
+                })?;
+
+            let mut used = HashSet::new();
+            collect_globals(
+                module,
+                &entry_point.function,
+                &mut used,
+                &mut HashSet::new(),
+            );
+
+            if entry_point.stage == ShaderStage::Vertex {
+                vertex_arguments = reflect_vertex_arguments(module, &entry_point.function)?;
+            }
+
+            // stages sharing a binding see the same resource
+            for binding in reflect_bindings(module, &used)? {
+                if !bindings
+                    .iter()
+                    .any(|e| e.group == binding.group && e.binding == binding.binding)
+                {
+                    bindings.push(binding);
+                }
+            }
+        }
+        bindings.sort_by_key(|e| (e.group, e.binding));
+
+        Ok(Self {
+            bindings,
+            vertex_arguments,
+        })
+    }
+
+    pub fn data_layout(&self, group: u32, binding: u32) -> Option<(&StructLayout, bool)> {
+        self.bindings
+            .iter()
+            .find(|e| e.group == group && e.binding == binding)
+            .and_then(|e| e.data.as_ref())
+            .map(|(layout, array)| (layout, *array))
+    }
+
+    // one layout per group up to the highest one used, unused groups stay empty
+    pub fn bind_group_layout_descriptors(
+        &self,
+    ) -> Result<Vec<BindGroupLayoutDescriptor>, ShaderLayoutError> {
+        let mut descriptors = Vec::new();
+        for binding in self.bindings.iter() {
+            while descriptors.len() <= binding.group as usize {
+                descriptors.push(BindGroupLayoutDescriptor {
+                    entries: Vec::new(),
+                });
+            }
+
+            let entries = &mut descriptors[binding.group as usize].entries;
+            if entries.len() != binding.binding as usize {
+                return Err(ShaderLayoutError::SparseBindings {
+                    group: binding.group,
+                    binding: binding.binding,
+                    name: binding.name.clone(),
+                });
+            }
+            entries.push(binding.ty.descriptor_entry());
+        }
+        Ok(descriptors)
+    }
+
+    // one buffer per vertex entry point argument, loose @location arguments share one buffer.
+    // only the struct arguments named in instance_arguments step per instance
+    pub fn vertex_descriptors(
+        &self,
+        instance_arguments: &[impl AsRef<str>],
+    ) -> Result<Vec<BufferLayout>, ShaderLayoutError> {
+        for name in instance_arguments.iter().map(AsRef::as_ref) {
+            if !self
+                .vertex_arguments
+                .iter()
+                .any(|e| e.name.as_deref() == Some(name))
+            {
+                return Err(ShaderLayoutError::MissingInstanceArgument {
+                    name: name.to_string(),
+                });
+            }
+        }
+
+        self.vertex_arguments
+            .iter()
+            .map(|argument| {
+                let entries = argument
+                    .inputs
+                    .iter()
+                    .map(|input| {
+                        Ok(BufferLayoutEntry {
+                            location: input.location,
+                            data_type: vertex_format(input)?,
+                            offset: None,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok(BufferLayout {
+                    step_mode: if instance_arguments
+                        .iter()
+                        .any(|e| argument.name.as_deref() == Some(e.as_ref()))
+                    {
+                        BufferLayoutStepMode::Instance
+                    } else {
+                        BufferLayoutStepMode::Vertex
+                    },
+                    entries,
+                    stride: None,
+                })
+            })
+            .collect()
+    }
+
+    pub fn validate_bind_groups(
+        &self,
+        descriptors: &[BindGroupLayoutDescriptor],
+    ) -> Result<(), ShaderLayoutError> {
+        for binding in self.bindings.iter() {
+            let Some(descriptor) = descriptors.get(binding.group as usize) else {
+                return Err(ShaderLayoutError::MissingGroup {
+                    group: binding.group,
+                    name: binding.name.clone(),
+                    given: descriptors.len(),
+                });
+            };
+
+            let Some(entry) = descriptor.entries.get(binding.binding as usize) else {
+                return Err(ShaderLayoutError::MissingBinding {
+                    group: binding.group,
+                    binding: binding.binding,
+                    name: binding.name.clone(),
+                    given: descriptor.entries.len(),
+                });
+            };
+
+            if !binding.ty.accepts(entry) {
+                return Err(ShaderLayoutError::BindingMismatch {
+                    group: binding.group,
+                    binding: binding.binding,
+                    name: binding.name.clone(),
+                    expected: binding.ty.to_string(),
+                    given: describe_entry(entry).to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    pub fn validate_vertex_descriptors(
+        &self,
+        descriptors: &[BufferLayout],
+    ) -> Result<(), ShaderLayoutError> {
+        let provided = descriptors
+            .iter()
+            .flat_map(|e| e.entries.iter())
+            .collect::<Vec<_>>();
+
+        for input in self.vertex_arguments.iter().flat_map(|e| e.inputs.iter()) {
+            let Some(entry) = provided.iter().find(|e| e.location == input.location) else {
+                return Err(ShaderLayoutError::MissingVertexInput {
+                    location: input.location,
+                    name: input.name.clone(),
+                });
+            };
+
+            let (kind, components) = format_shape(entry.data_type);
+            if kind != input.kind || components != input.components {
+                return Err(ShaderLayoutError::VertexInputMismatch {
+                    location: input.location,
+                    name: input.name.clone(),
+                    expected: describe_shape(input.kind, input.components),
+                    given: describe_shape(kind, components),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ShaderDescriptor {
+    // nothing is reflected unless the descriptor asks for it or the layouts should be validated
+    pub(crate) fn resolve_layouts(
+        &mut self,
+        shader: &CompiledShader,
+        validate: bool,
+    ) -> Result<Option<ShaderReflection>, ShaderLayoutError> {
+        if !self.reflect && !validate {
+            return Ok(None);
+        }
+        let reflection =
+            ShaderReflection::from_stages(&shader.stages(&[&self.vs_entry, &self.fs_entry]))?;
+
+        if self.reflect && self.bind_group_layout_descriptors.is_empty() {
+            self.bind_group_layout_descriptors = reflection.bind_group_layout_descriptors()?;
+        }
+        if self.reflect && self.descriptors.is_empty() {
+            self.descriptors = reflection.vertex_descriptors(&self.instance_arguments)?;
+        }
+
+        reflection.validate_bind_groups(&self.bind_group_layout_descriptors)?;
+        reflection.validate_vertex_descriptors(&self.descriptors)?;
+        Ok(Some(reflection))
+    }
+}
+
+impl ComputeShaderDescriptor {
+    pub(crate) fn resolve_layouts(
+        &mut self,
+        shader: &CompiledShader,
+        validate: bool,
+    ) -> Result<Option<ShaderReflection>, ShaderLayoutError> {
+        if !self.reflect && !validate {
+            return Ok(None);
+        }
+        let reflection = ShaderReflection::from_stages(&shader.stages(&[&self.entry]))?;
+
+        if self.reflect && self.bind_group_layout_descriptors.is_empty() {
+            self.bind_group_layout_descriptors = reflection.bind_group_layout_descriptors()?;
+        }
+
+        reflection.validate_bind_groups(&self.bind_group_layout_descriptors)?;
+        Ok(Some(reflection))
+    }
+}
+
+fn reflect_bindings(
+    module: &naga::Module,
+    used: &HashSet<Handle<naga::GlobalVariable>>,
+) -> Result<Vec<ShaderBinding>, ShaderLayoutError> {
+    let mut bindings = Vec::new();
+    for (handle, global) in module.global_variables.iter() {
+        let Some(binding) = &global.binding else {
+            continue;
+        };
+        if !used.contains(&handle) {
+            continue;
+        }
+
+        let name = global.name.clone().unwrap_or_default();
+        let ty = match (&global.space, &module.types[global.ty].inner) {
+            (AddressSpace::Uniform, _) => ShaderBindingType::Uniform,
+            (AddressSpace::Storage { access }, _) => ShaderBindingType::Storage {
+                read_only: !access.contains(naga::StorageAccess::STORE),
+            },
+            (
+                AddressSpace::Handle,
+                TypeInner::Image {
+                    class: ImageClass::Sampled { kind, .. },
+                    ..
+                },
+            ) => ShaderBindingType::Texture {
+                sample_type: match kind {
+                    ScalarKind::Uint => TextureSampleType::Uint,
+                    ScalarKind::Sint => TextureSampleType::Sint,
+                    _ => TextureSampleType::Filterable,
+                },
+            },
+            (
+                AddressSpace::Handle,
+                TypeInner::Image {
+                    class: ImageClass::Depth { .. },
+                    ..
+                },
+            ) => ShaderBindingType::Texture {
+                sample_type: TextureSampleType::Depth,
+            },
+            (AddressSpace::Handle, TypeInner::Sampler { comparison }) => {
+                ShaderBindingType::Sampler {
+                    comparison: *comparison,
+                }
+            }
+            _ => {
+                return Err(ShaderLayoutError::UnsupportedBinding {
+                    group: binding.group,
+                    binding: binding.binding,
+                    name,
+                })
+            }
+        };
+
+        let data = match ty {
+            ShaderBindingType::Uniform | ShaderBindingType::Storage { .. } => {
+                reflect_data_layout(module, global.ty)
+            }
+            _ => None,
+        };
+
+        bindings.push(ShaderBinding {
+            group: binding.group,
+            binding: binding.binding,
+            name,
+            ty,
+            data,
+        });
+    }
+    Ok(bindings)
+}
+
+fn reflect_data_layout(
+    module: &naga::Module,
+    ty: Handle<naga::Type>,
+) -> Option<(StructLayout, bool)> {
+    let (ty, stride) = match &module.types[ty].inner {
+        TypeInner::Array { base, stride, .. } => (*base, Some(*stride)),
+        _ => (ty, None),
+    };
+    let TypeInner::Struct { members, span } = &module.types[ty].inner else {
+        return None;
+    };
+
+    let fields = members
+        .iter()
+        .map(|e| FieldLayout {
+            name: e.name.clone().unwrap_or_default(),
+            offset: e.offset as u64,
+            size: module.types[e.ty].inner.size(module.to_ctx()) as u64,
+        })
+        .collect();
+
+    let layout = StructLayout {
+        name: module.types[ty].name.clone().unwrap_or_default(),
+        size: stride.unwrap_or(*span) as u64,
+        fields,
+    };
+    Some((layout, stride.is_some()))
+}
+
+fn collect_globals(
+    module: &naga::Module,
+    function: &naga::Function,
+    used: &mut HashSet<Handle<naga::GlobalVariable>>,
+    visited: &mut HashSet<Handle<naga::Function>>,
+) {
+    for (_, expression) in function.expressions.iter() {
+        if let naga::Expression::GlobalVariable(handle) = expression {
+            used.insert(*handle);
+        }
+    }
+
+    let mut called = Vec::new();
+    collect_calls(&function.body, &mut called);
+    for handle in called {
+        if visited.insert(handle) {
+            collect_globals(module, &module.functions[handle], used, visited);
+        }
+    }
+}
+
+fn collect_calls(block: &naga::Block, called: &mut Vec<Handle<naga::Function>>) {
+    for statement in block.iter() {
+        match statement {
+            naga::Statement::Call { function, .. } => called.push(*function),
+            naga::Statement::Block(block) => collect_calls(block, called),
+            naga::Statement::If { accept, reject, .. } => {
+                collect_calls(accept, called);
+                collect_calls(reject, called);
+            }
+            naga::Statement::Switch { cases, .. } => {
+                for case in cases {
+                    collect_calls(&case.body, called);
+                }
+            }
+            naga::Statement::Loop {
+                body, continuing, ..
+            } => {
+                collect_calls(body, called);
+                collect_calls(continuing, called);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn reflect_vertex_arguments(
+    module: &naga::Module,
+    function: &naga::Function,
+) -> Result<Vec<VertexArgument>, ShaderLayoutError> {
+    let mut arguments = Vec::new();
+    let mut loose = Vec::new();
+
+    for argument in function.arguments.iter() {
+        let name = argument.name.clone().unwrap_or_default();
+        let ty = &module.types[argument.ty];
+
+        match (&argument.binding, &ty.inner) {
+            (Some(Binding::Location { location, .. }), inner) => {
+                loose.push(vertex_input(*location, name, inner)?);
+            }
+            (None, TypeInner::Struct { members, .. }) => {
+                let mut inputs = Vec::new();
+                for member in members {
+                    if let Some(Binding::Location { location, .. }) = member.binding {
+                        inputs.push(vertex_input(
+                            location,
+                            member.name.clone().unwrap_or_default(),
+                            &module.types[member.ty].inner,
+                        )?);
+                    }
+                }
+
+                if !inputs.is_empty() {
+                    arguments.push(VertexArgument {
+                        name: Some(name),
+                        inputs,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !loose.is_empty() {
+        arguments.insert(
+            0,
+            VertexArgument {
+                name: None,
+                inputs: loose,
+            },
+        );
+    }
+
+    Ok(arguments)
+}
+
+fn vertex_input(
+    location: u32,
+    name: String,
+    inner: &TypeInner,
+) -> Result<VertexInput, ShaderLayoutError> {
+    let (scalar, components) = match inner {
+        TypeInner::Scalar(scalar) => (scalar, 1),
+        TypeInner::Vector { size, scalar } => (scalar, *size as u32),
+        _ => return Err(ShaderLayoutError::UnsupportedVertexInput { location, name }),
+    };
+
+    match scalar.kind {
+        ScalarKind::Float | ScalarKind::Uint | ScalarKind::Sint if scalar.width == 4 => {
+            Ok(VertexInput {
+                location,
+                name,
+                kind: scalar.kind,
+                components,
+            })
+        }
+        _ => Err(ShaderLayoutError::UnsupportedVertexInput { location, name }),
+    }
+}
+
+fn vertex_format(input: &VertexInput) -> Result<BufferLayoutEntryDataType, ShaderLayoutError> {
+    let format = match (input.kind, input.components) {
+        (ScalarKind::Float, 1) => BufferLayoutEntryDataType::Float32,
+        (ScalarKind::Float, 2) => BufferLayoutEntryDataType::Float32x2,
+        (ScalarKind::Float, 3) => BufferLayoutEntryDataType::Float32x3,
+        (ScalarKind::Float, 4) => BufferLayoutEntryDataType::Float32x4,
+        (ScalarKind::Uint, 1) => BufferLayoutEntryDataType::U32,
+        (ScalarKind::Uint, 2) => BufferLayoutEntryDataType::Uint32x2,
+        (ScalarKind::Uint, 3) => BufferLayoutEntryDataType::Uint32x3,
+        (ScalarKind::Uint, 4) => BufferLayoutEntryDataType::Uint32x4,
+        (ScalarKind::Sint, 1) => BufferLayoutEntryDataType::Sint32,
+        (ScalarKind::Sint, 2) => BufferLayoutEntryDataType::Sint32x2,
+        (ScalarKind::Sint, 3) => BufferLayoutEntryDataType::Sint32x3,
+        (ScalarKind::Sint, 4) => BufferLayoutEntryDataType::Sint32x4,
+        _ => {
+            return Err(ShaderLayoutError::UnsupportedVertexInput {
+                location: input.location,
+                name: input.name.clone(),
+            })
+        }
+    };
+    Ok(format)
+}
+
+// the scalar kind and component count a vertex format shows up as in the shader
+fn format_shape(data_type: BufferLayoutEntryDataType) -> (ScalarKind, u32) {
+    use BufferLayoutEntryDataType as T;
+    match data_type {
+        T::Float32 => (ScalarKind::Float, 1),
+        T::Float32x2 | T::Unorm8x2 | T::Snorm8x2 | T::Unorm16x2 | T::Snorm16x2 | T::Float16x2 => {
+            (ScalarKind::Float, 2)
+        }
+        T::Float32x3 => (ScalarKind::Float, 3),
+        T::Float32x4
+        | T::Unorm8x4
+        | T::Snorm8x4
+        | T::Unorm16x4
+        | T::Snorm16x4
+        | T::Float16x4
+        | T::Unorm10_10_10_2 => (ScalarKind::Float, 4),
+        T::U32 => (ScalarKind::Uint, 1),
+        T::Uint32x2 | T::Uint8x2 | T::Uint16x2 => (ScalarKind::Uint, 2),
+        T::Uint32x3 => (ScalarKind::Uint, 3),
+        T::Uint32x4 | T::Uint8x4 | T::Uint16x4 => (ScalarKind::Uint, 4),
+        T::Sint32 => (ScalarKind::Sint, 1),
+        T::Sint32x2 | T::Sint8x2 | T::Sint16x2 => (ScalarKind::Sint, 2),
+        T::Sint32x3 => (ScalarKind::Sint, 3),
+        T::Sint32x4 | T::Sint8x4 | T::Sint16x4 => (ScalarKind::Sint, 4),
+    }
+}
+
+fn describe_shape(kind: ScalarKind, components: u32) -> String {
+    let scalar = match kind {
+        ScalarKind::Float => "f32",
+        ScalarKind::Uint => "u32",
+        ScalarKind::Sint => "i32",
+        _ => "?",
+    };
+    if components == 1 {
+        scalar.to_string()
+    } else {
+        format!("vec{components}<{scalar}>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHADER: &str = "
+        struct Camera {
+            view_proj: mat4x4<f32>,
+        }
+
+        struct Light {
+            direction: vec3<f32>,
+        }
+
+        struct VertexInput {
+            @location(0) position: vec3<f32>,
+            @location(1) uv: vec2<f32>,
+        }
+
+        struct InstanceInput {
+            @location(5) offset: vec4<f32>,
+            @location(6) index: u32,
+        }
+
+        @group(0) @binding(0) var<uniform> camera: Camera;
+        @group(1) @binding(0) var<storage, read> lights: array<Light>;
+        @group(1) @binding(1) var diffuse: texture_2d<f32>;
+        @group(1) @binding(2) var diffuse_sampler: sampler;
+
+        @vertex
+        fn vs_main(vertex: VertexInput, instance: InstanceInput) -> @builtin(position) vec4<f32> {
+            return camera.view_proj * vec4<f32>(vertex.position, 1.0) + instance.offset;
+        }
+
+        @fragment
+        fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+            let color = textureSample(diffuse, diffuse_sampler, position.xy);
+            return color * vec4<f32>(lights[0].direction, 1.0);
+        }
+    ";
+
+    fn reflect() -> ShaderReflection {
+        ShaderReflection::from_wgsl(SHADER, &["vs_main", "fs_main"]).unwrap()
+    }
+
+    fn data(is_uniform: bool, read_only: bool) -> BindGroupLayoutDescriptorEntry {
+        BindGroupLayoutDescriptorEntry::Data {
+            is_uniform,
+            read_only,
+        }
+    }
+
+    fn entry(location: u32, data_type: BufferLayoutEntryDataType) -> BufferLayoutEntry {
+        BufferLayoutEntry {
+            location,
+            data_type,
+            offset: None,
+        }
+    }
+
+    fn buffer(step_mode: BufferLayoutStepMode, entries: Vec<BufferLayoutEntry>) -> BufferLayout {
+        BufferLayout {
+            step_mode,
+            entries,
+            stride: None,
+        }
+    }
+
+    fn vertex_buffers(position: BufferLayoutEntryDataType) -> Vec<BufferLayout> {
+        vec![
+            buffer(
+                BufferLayoutStepMode::Vertex,
+                vec![
+                    entry(0, position),
+                    entry(1, BufferLayoutEntryDataType::Float32x2),
+                ],
+            ),
+            buffer(
+                BufferLayoutStepMode::Instance,
+                vec![
+                    entry(5, BufferLayoutEntryDataType::Float32x4),
+                    entry(6, BufferLayoutEntryDataType::U32),
+                ],
+            ),
+        ]
+    }
+
+    #[test]
+    fn from_wgsl_missing_entry_point() {
+        let err = ShaderReflection::from_wgsl(SHADER, &["vs_main", "main"]).unwrap_err();
+        assert!(matches!(err, ShaderLayoutError::MissingEntryPoint { entry } if entry == "main"));
+    }
+
+    #[test]
+    fn from_wgsl_parse_error() {
+        let err = ShaderReflection::from_wgsl("fn broken( {", &["main"]).unwrap_err();
+        assert!(matches!(err, ShaderLayoutError::Parse(_)));
+    }
+
+    #[test]
+    fn bind_group_layout_descriptors() {
+        let descriptors = reflect().bind_group_layout_descriptors().unwrap();
+        assert_eq!(
+            descriptors,
+            vec![
+                BindGroupLayoutDescriptor {
+                    entries: vec![data(true, false)],
+                },
+                BindGroupLayoutDescriptor {
+                    entries: vec![
+                        data(false, true),
+                        BindGroupLayoutDescriptorEntry::Texture {
+                            sample_type: TextureSampleType::Filterable,
+                        },
+                        BindGroupLayoutDescriptorEntry::Sampler {
+                            sampler_type: SamplerType::Filtering,
+                        },
+                    ],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn bind_group_layout_descriptors_unused_group() {
+        let code = "
+            @group(1) @binding(0) var<uniform> scale: vec4<f32>;
+
+            @compute @workgroup_size(1)
+            fn main() {
+                let a = scale;
+            }
+        ";
+        let descriptors = ShaderReflection::from_wgsl(code, &["main"])
+            .unwrap()
+            .bind_group_layout_descriptors()
+            .unwrap();
+        assert_eq!(descriptors.len(), 2);
+        assert!(descriptors[0].entries.is_empty());
+        assert_eq!(descriptors[1].entries, vec![data(true, false)]);
+    }
+
+    #[test]
+    fn bind_group_layout_descriptors_sparse() {
+        let code = "
+            @group(0) @binding(0) var<uniform> a: vec4<f32>;
+            @group(0) @binding(2) var<uniform> b: vec4<f32>;
+
+            @compute @workgroup_size(1)
+            fn main() {
+                let c = a + b;
+            }
+        ";
+        let err = ShaderReflection::from_wgsl(code, &["main"])
+            .unwrap()
+            .bind_group_layout_descriptors()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ShaderLayoutError::SparseBindings { group: 0, binding: 2, name } if name == "b"
+        ));
+    }
+
+    #[test]
+    fn vertex_descriptors() {
+        let descriptors = reflect().vertex_descriptors(&["instance"]).unwrap();
+        let expected = vertex_buffers(BufferLayoutEntryDataType::Float32x3);
+        assert_eq!(descriptors.len(), expected.len());
+        for (descriptor, expected) in descriptors.iter().zip(expected.iter()) {
+            assert_eq!(descriptor.step_mode, expected.step_mode);
+            assert_eq!(descriptor.entries, expected.entries);
+        }
+    }
+
+    #[test]
+    fn vertex_descriptors_without_instance_arguments() {
+        let descriptors = reflect().vertex_descriptors(&[] as &[&str]).unwrap();
+        assert!(descriptors
+            .iter()
+            .all(|e| e.step_mode == BufferLayoutStepMode::Vertex));
+    }
+
+    #[test]
+    fn vertex_descriptors_missing_instance_argument() {
+        let err = reflect().vertex_descriptors(&["instances"]).unwrap_err();
+        assert!(
+            matches!(err, ShaderLayoutError::MissingInstanceArgument { name } if name == "instances")
+        );
+    }
+
+    #[test]
+    fn validate_bind_groups() {
+        let reflection = reflect();
+        let descriptors = reflection.bind_group_layout_descriptors().unwrap();
+        reflection.validate_bind_groups(&descriptors).unwrap();
+
+        // a read only binding is fine with a writable layout
+        let mut writable = descriptors.clone();
+        writable[1].entries[0] = data(false, false);
+        reflection.validate_bind_groups(&writable).unwrap();
+    }
+
+    #[test]
+    fn validate_bind_groups_missing_group() {
+        let reflection = reflect();
+        let mut descriptors = reflection.bind_group_layout_descriptors().unwrap();
+        descriptors.pop();
+        let err = reflection.validate_bind_groups(&descriptors).unwrap_err();
+        assert!(matches!(
+            err,
+            ShaderLayoutError::MissingGroup {
+                group: 1,
+                given: 1,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn validate_bind_groups_missing_binding() {
+        let reflection = reflect();
+        let mut descriptors = reflection.bind_group_layout_descriptors().unwrap();
+        descriptors[1].entries.pop();
+        let err = reflection.validate_bind_groups(&descriptors).unwrap_err();
+        assert!(matches!(
+            err,
+            ShaderLayoutError::MissingBinding { group: 1, binding: 2, given: 2, name } if name == "diffuse_sampler"
+        ));
+    }
+
+    #[test]
+    fn validate_bind_groups_mismatch() {
+        let reflection = reflect();
+        let mut descriptors = reflection.bind_group_layout_descriptors().unwrap();
+        descriptors[0].entries[0] = data(false, true);
+        let err = reflection.validate_bind_groups(&descriptors).unwrap_err();
+        assert!(matches!(
+            err,
+            ShaderLayoutError::BindingMismatch { group: 0, binding: 0, name, expected, given }
+                if name == "camera" && expected == "uniform buffer" && given == "read only storage buffer"
+        ));
+    }
+
+    #[test]
+    fn validate_vertex_descriptors() {
+        let reflection = reflect();
+        reflection
+            .validate_vertex_descriptors(&vertex_buffers(BufferLayoutEntryDataType::Float32x3))
+            .unwrap();
+    }
+
+    #[test]
+    fn validate_vertex_descriptors_missing_input() {
+        let reflection = reflect();
+        let mut descriptors = vertex_buffers(BufferLayoutEntryDataType::Float32x3);
+        descriptors[1].entries.pop();
+        let err = reflection
+            .validate_vertex_descriptors(&descriptors)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ShaderLayoutError::MissingVertexInput { location: 6, name } if name == "index"
+        ));
+    }
+
+    #[test]
+    fn validate_vertex_descriptors_wrong_kind() {
+        let reflection = reflect();
+        let err = reflection
+            .validate_vertex_descriptors(&vertex_buffers(BufferLayoutEntryDataType::Uint32x3))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ShaderLayoutError::VertexInputMismatch { location: 0, expected, given, .. }
+                if expected == "vec3<f32>" && given == "vec3<u32>"
+        ));
+    }
+
+    #[test]
+    fn validate_vertex_descriptors_wrong_components() {
+        let reflection = reflect();
+        let err = reflection
+            .validate_vertex_descriptors(&vertex_buffers(BufferLayoutEntryDataType::Float32x4))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ShaderLayoutError::VertexInputMismatch { location: 0, expected, given, .. }
+                if expected == "vec3<f32>" && given == "vec4<f32>"
+        ));
+    }
+}
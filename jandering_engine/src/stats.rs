@@ -0,0 +1,31 @@
+use std::collections::BTreeMap;
+
+// counted as passes are submitted, a frame ends with present()
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    // render and compute passes
+    pub passes: u32,
+    pub draw_calls: u32,
+    pub instances: u64,
+    pub triangles: u64,
+    pub dispatches: u32,
+    // only binds that change what was bound before in the same pass are counted
+    pub pipeline_switches: u32,
+    pub bind_group_switches: u32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    pub buffer_bytes: u64,
+    pub texture_bytes: u64,
+    pub buffer_bytes_by_label: BTreeMap<String, u64>,
+    pub texture_bytes_by_label: BTreeMap<String, u64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RendererStats {
+    // the last frame that was presented
+    pub frame: FrameStats,
+    // what's allocated right now
+    pub memory: MemoryStats,
+}
@@ -0,0 +1,59 @@
+use crate::texture::TextureFormat;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PresentMode {
+    // fall back to whatever is supported, the two set by FpsPreference
+    AutoVsync,
+    AutoNoVsync,
+    Fifo,
+    FifoRelaxed,
+    Mailbox,
+    Immediate,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AlphaMode {
+    Auto,
+    Opaque,
+    PreMultiplied,
+    PostMultiplied,
+    Inherit,
+}
+
+// shaders drawing to the screen need `target_texture_format` to match `format`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SurfaceConfig {
+    pub present_mode: PresentMode,
+    pub format: TextureFormat,
+    pub alpha_mode: AlphaMode,
+    // how many frames can be queued up before present() waits
+    pub frame_latency: u32,
+    // above 1 passes drawing to the screen render into hidden multisampled targets
+    // that get resolved to it, shaders drawing to the screen are compiled for it when it's set
+    pub msaa_samples: u32,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SurfaceCapabilities {
+    pub present_modes: Vec<PresentMode>,
+    pub formats: Vec<TextureFormat>,
+    pub alpha_modes: Vec<AlphaMode>,
+    // usable with every format and the screen's depth
+    pub msaa_samples: Vec<u32>,
+}
+
+impl SurfaceCapabilities {
+    pub fn supports(&self, config: &SurfaceConfig) -> bool {
+        let present_mode = matches!(
+            config.present_mode,
+            PresentMode::AutoVsync | PresentMode::AutoNoVsync
+        ) || self.present_modes.contains(&config.present_mode);
+        let alpha_mode =
+            config.alpha_mode == AlphaMode::Auto || self.alpha_modes.contains(&config.alpha_mode);
+
+        present_mode
+            && alpha_mode
+            && self.formats.contains(&config.format)
+            && self.msaa_samples.contains(&config.msaa_samples)
+    }
+}
@@ -1,3 +1,7 @@
+use std::borrow::Cow;
+
+use je_windowing::WindowId;
+
 use crate::types::UVec2;
 
 pub mod sampler;
@@ -10,10 +14,12 @@ pub struct Texture {
     pub height: u32,
 }
 
-#[derive(Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum TextureFormat {
     Rgba8U,
     Bgra8U,
+    // half float, for hdr surfaces
+    Rgba16F,
     F32,
     Depth32F,
     Depth16U,
@@ -31,10 +37,30 @@ pub mod texture_usage {
     pub const TARGET: TextureUsage = 1 << 3;
 }
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TextureSize {
+    Absolute(UVec2),
+    // the window's surface size times the scale, resized along with it.
+    // until the window is registered the texture is 1x1
+    Screen(WindowId, f32),
+}
+
+impl From<UVec2> for TextureSize {
+    fn from(value: UVec2) -> Self {
+        Self::Absolute(value)
+    }
+}
+
+impl From<(u32, u32)> for TextureSize {
+    fn from(value: (u32, u32)) -> Self {
+        Self::Absolute(value.into())
+    }
+}
+
 #[derive(Clone)]
 pub struct TextureDescriptor<'data> {
-    pub name: &'static str,
-    pub size: UVec2,
+    pub name: Cow<'static, str>,
+    pub size: TextureSize,
     pub sample_count: u32,
     pub data: Option<&'data [u8]>,
     pub format: TextureFormat,
@@ -44,8 +70,8 @@ pub struct TextureDescriptor<'data> {
 impl<'data> Default for TextureDescriptor<'data> {
     fn default() -> Self {
         Self {
-            name: "texture",
-            size: UVec2::new(8, 8),
+            name: "texture".into(),
+            size: UVec2::new(8, 8).into(),
             sample_count: 1,
             format: TextureFormat::Bgra8U,
             data: None,
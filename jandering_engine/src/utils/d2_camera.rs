@@ -1,8 +1,5 @@
 use crate::{
-    bind_group::{
-        BindGroup, BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutDescriptorEntry,
-        BindGroupLayoutEntry,
-    },
+    bind_group::{BindGroup, BindGroupLayout, BindGroupLayoutEntry},
     renderer::{BufferHandle, Janderer, Renderer},
     types::{UVec2, Vec2},
 };
@@ -28,6 +25,7 @@ pub struct D2CameraController {
     pub pan_offset: Vec2,
 }
 
+#[derive(BindGroup)]
 pub struct D2CameraBindGroup {
     pub controller: Option<D2CameraController>,
 
@@ -41,26 +39,10 @@ pub struct D2CameraBindGroup {
     mouse_is_inside: bool,
     pub right_click_move: bool,
 
+    #[uniform]
     buffer_handle: BufferHandle,
 }
 
-impl BindGroup for D2CameraBindGroup {
-    fn get_layout(&self) -> BindGroupLayout {
-        BindGroupLayout {
-            entries: vec![BindGroupLayoutEntry::Data(self.buffer_handle)],
-        }
-    }
-
-    fn get_layout_descriptor() -> crate::bind_group::BindGroupLayoutDescriptor
-    where
-        Self: Sized,
-    {
-        BindGroupLayoutDescriptor {
-            entries: vec![BindGroupLayoutDescriptorEntry::Data { is_uniform: true }],
-        }
-    }
-}
-
 impl D2CameraBindGroup {
     pub fn resize(&mut self, resolution: UVec2) {
         self.resolution = Vec2::new(resolution.x as f32, resolution.y as f32);
@@ -131,12 +113,11 @@ impl D2CameraBindGroup {
                 }
                 WindowEvent::MouseEntered => self.mouse_is_inside = true,
                 WindowEvent::MouseLeft => self.mouse_is_inside = false,
-                WindowEvent::MouseInput { state, button, .. } => {
+                WindowEvent::MouseInput { state, button, .. }
                     if (self.right_click_move && *button == MouseButton::Right)
-                        || (!self.right_click_move && *button == MouseButton::Left)
-                    {
-                        self.pressing = matches!(state, InputState::Pressed);
-                    }
+                        || (!self.right_click_move && *button == MouseButton::Left) =>
+                {
+                    self.pressing = matches!(state, InputState::Pressed);
                 }
                 _ => {}
             }
@@ -1,9 +1,7 @@
 use crate::{
-    bind_group::{
-        BindGroup, BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutDescriptorEntry,
-        BindGroupLayoutEntry,
-    },
+    bind_group::{BindGroup, BindGroupLayoutDescriptor},
     renderer::{BindGroupHandle, BufferHandle, Janderer, Renderer, UntypedBindGroupHandle},
+    shader::layout::DataLayout,
     types::{Mat4, Vec2, Vec3},
 };
 
@@ -30,7 +28,7 @@ pub const CAMERA_UP: Vec3 = Vec3 {
 };
 
 #[repr(C)]
-#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, DataLayout)]
 pub struct CameraData {
     up: Vec3,
     up_padding: f32,
@@ -65,7 +63,9 @@ pub trait CameraController {
     fn set_direction(&mut self, _direction: Vec3) {}
 }
 
+#[derive(BindGroup)]
 struct MatrixCameraBindGroup {
+    #[uniform]
     buffer_handle: BufferHandle,
 }
 
@@ -82,20 +82,6 @@ impl std::fmt::Debug for MatrixCamera {
     }
 }
 
-impl BindGroup for MatrixCameraBindGroup {
-    fn get_layout_descriptor() -> BindGroupLayoutDescriptor {
-        BindGroupLayoutDescriptor {
-            entries: vec![BindGroupLayoutDescriptorEntry::Data { is_uniform: true }],
-        }
-    }
-
-    fn get_layout(&self) -> BindGroupLayout {
-        BindGroupLayout {
-            entries: vec![BindGroupLayoutEntry::Data(self.buffer_handle)],
-        }
-    }
-}
-
 impl MatrixCamera {
     pub fn new(renderer: &mut Renderer) -> Self {
         let data = CameraData {
@@ -111,7 +97,7 @@ impl MatrixCamera {
             inverse_view: Mat4::IDENTITY,
         };
 
-        let buffer_handle = renderer.create_uniform_buffer(bytemuck::cast_slice(&[data]));
+        let buffer_handle = renderer.create_typed_uniform_buffer(&data);
 
         let bind_group = renderer.create_typed_bind_group(MatrixCameraBindGroup { buffer_handle });
 
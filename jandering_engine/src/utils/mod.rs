@@ -14,6 +14,8 @@ pub const SQRT_3: f32 = 1.732_050_8;
 #[derive(Clone)]
 pub enum FilePath {
     FileName(&'static str),
+    // same as FileName, for names only known at runtime
+    OwnedFileName(String),
     AbsolutePath(PathBuf),
 }
 
@@ -31,20 +33,23 @@ fn format_url(file_name: &str) -> reqwest::Url {
 pub async fn load_binary(file: FilePath) -> anyhow::Result<Vec<u8>> {
     cfg_if::cfg_if! {
         if #[cfg(target_arch = "wasm32")] {
-            match file {
-                FilePath::FileName(name) => { let url = format_url(name);
-                let v = reqwest::get(url)
-                    .await?
-                    .bytes()
-                    .await?
-                    .to_vec();
-                Ok(v)},
-                FilePath::AbsolutePath(path) => Err(anyhow::anyhow!(format!("Can't read filepaths on the web {:?}", path))),
-            }
+            let name = match &file {
+                FilePath::FileName(name) => *name,
+                FilePath::OwnedFileName(name) => name.as_str(),
+                FilePath::AbsolutePath(path) => return Err(anyhow::anyhow!(format!("Can't read filepaths on the web {:?}", path))),
+            };
+            let url = format_url(name);
+            let v = reqwest::get(url)
+                .await?
+                .bytes()
+                .await?
+                .to_vec();
+            Ok(v)
         } else {
            use std::path::Path;
             let path = match file {
                 FilePath::FileName(name) => Path::new("res").join(name),
+                FilePath::OwnedFileName(name) => Path::new("res").join(name),
                 FilePath::AbsolutePath(path) => path,
             };
 
@@ -56,19 +61,22 @@ pub async fn load_binary(file: FilePath) -> anyhow::Result<Vec<u8>> {
 pub async fn load_text(file: FilePath) -> anyhow::Result<String> {
     cfg_if::cfg_if! {
         if #[cfg(target_arch = "wasm32")] {
-            match file {
-                FilePath::FileName(name) => { let url = format_url(name);
-                let v = reqwest::get(url)
-                    .await?
-                    .text()
-                    .await?;
-                Ok(v)},
-                FilePath::AbsolutePath(path) => Err(anyhow::anyhow!(format!("Can't read filepaths on the web {:?}", path))),
-            }
+            let name = match &file {
+                FilePath::FileName(name) => *name,
+                FilePath::OwnedFileName(name) => name.as_str(),
+                FilePath::AbsolutePath(path) => return Err(anyhow::anyhow!(format!("Can't read filepaths on the web {:?}", path))),
+            };
+            let url = format_url(name);
+            let v = reqwest::get(url)
+                .await?
+                .text()
+                .await?;
+            Ok(v)
         } else {
            use std::path::Path;
             let path = match file {
                 FilePath::FileName(name) => Path::new("res").join(name),
+                FilePath::OwnedFileName(name) => Path::new("res").join(name),
                 FilePath::AbsolutePath(path) => path,
             };
 
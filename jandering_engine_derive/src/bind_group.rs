@@ -0,0 +1,204 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Fields, Ident, Meta, Result};
+
+enum Binding {
+    Uniform,
+    Storage { read_only: bool },
+    Texture { sample_type: TokenStream },
+    Sampler { sampler_type: TokenStream },
+}
+
+pub fn expand(input: DeriveInput) -> Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "BindGroup can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "BindGroup can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut layout_entries = Vec::new();
+    let mut descriptor_entries = Vec::new();
+
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+
+        let mut binding = None;
+        for attr in &field.attrs {
+            let parsed = if attr.path().is_ident("uniform") {
+                parse_uniform(&attr.meta)?
+            } else if attr.path().is_ident("storage") {
+                parse_storage(&attr.meta)?
+            } else if attr.path().is_ident("texture") {
+                parse_texture(&attr.meta)?
+            } else if attr.path().is_ident("sampler") {
+                parse_sampler(&attr.meta)?
+            } else {
+                continue;
+            };
+
+            if binding.is_some() {
+                return Err(Error::new_spanned(
+                    attr,
+                    "a field can only have one binding attribute",
+                ));
+            }
+            binding = Some(parsed);
+        }
+
+        let Some(binding) = binding else {
+            continue;
+        };
+
+        let (layout_entry, descriptor_entry) = entries(ident, binding);
+        layout_entries.push(layout_entry);
+        descriptor_entries.push(descriptor_entry);
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::jandering_engine::bind_group::BindGroup for #name #ty_generics #where_clause {
+            fn get_layout_descriptor() -> ::jandering_engine::bind_group::BindGroupLayoutDescriptor {
+                ::jandering_engine::bind_group::BindGroupLayoutDescriptor {
+                    entries: vec![#(#descriptor_entries),*],
+                }
+            }
+
+            fn get_layout(&self) -> ::jandering_engine::bind_group::BindGroupLayout {
+                ::jandering_engine::bind_group::BindGroupLayout {
+                    entries: vec![#(#layout_entries),*],
+                }
+            }
+        }
+    })
+}
+
+fn entries(ident: &Ident, binding: Binding) -> (TokenStream, TokenStream) {
+    let path = quote!(::jandering_engine::bind_group);
+    match binding {
+        Binding::Uniform => (
+            quote!(#path::BindGroupLayoutEntry::Data(self.#ident)),
+            quote!(#path::BindGroupLayoutDescriptorEntry::Data {
+                is_uniform: true,
+                read_only: false,
+            }),
+        ),
+        Binding::Storage { read_only: false } => (
+            quote!(#path::BindGroupLayoutEntry::Data(self.#ident)),
+            quote!(#path::BindGroupLayoutDescriptorEntry::Data {
+                is_uniform: false,
+                read_only: false,
+            }),
+        ),
+        Binding::Storage { read_only: true } => (
+            quote!(#path::BindGroupLayoutEntry::ReadOnlyData(self.#ident)),
+            quote!(#path::BindGroupLayoutDescriptorEntry::Data {
+                is_uniform: false,
+                read_only: true,
+            }),
+        ),
+        Binding::Texture { sample_type } => (
+            quote!(#path::BindGroupLayoutEntry::Texture {
+                handle: self.#ident,
+                sample_type: #path::TextureSampleType::#sample_type,
+            }),
+            quote!(#path::BindGroupLayoutDescriptorEntry::Texture {
+                sample_type: #path::TextureSampleType::#sample_type,
+            }),
+        ),
+        Binding::Sampler { sampler_type } => (
+            quote!(#path::BindGroupLayoutEntry::Sampler {
+                handle: self.#ident,
+                sampler_type: #path::SamplerType::#sampler_type,
+            }),
+            quote!(#path::BindGroupLayoutDescriptorEntry::Sampler {
+                sampler_type: #path::SamplerType::#sampler_type,
+            }),
+        ),
+    }
+}
+
+fn parse_uniform(meta: &Meta) -> Result<Binding> {
+    match meta {
+        Meta::Path(_) => Ok(Binding::Uniform),
+        _ => Err(Error::new_spanned(meta, "#[uniform] takes no arguments")),
+    }
+}
+
+fn parse_storage(meta: &Meta) -> Result<Binding> {
+    let mut read_only = false;
+    parse_flags(meta, |flag| match flag.to_string().as_str() {
+        "read_only" => {
+            read_only = true;
+            Ok(())
+        }
+        _ => Err(Error::new_spanned(
+            flag,
+            "unknown storage option, expected `read_only`",
+        )),
+    })?;
+    Ok(Binding::Storage { read_only })
+}
+
+fn parse_texture(meta: &Meta) -> Result<Binding> {
+    let mut sample_type = quote!(Filterable);
+    parse_flags(meta, |flag| {
+        sample_type =
+            match flag.to_string().as_str() {
+                "filterable" => quote!(Filterable),
+                "non_filterable" => quote!(NonFilterable),
+                "depth" => quote!(Depth),
+                "uint" => quote!(Uint),
+                "sint" => quote!(Sint),
+                _ => return Err(Error::new_spanned(
+                    flag,
+                    "unknown texture option, expected `filterable`, `non_filterable`, `depth`, `uint` or `sint`",
+                )),
+            };
+        Ok(())
+    })?;
+    Ok(Binding::Texture { sample_type })
+}
+
+fn parse_sampler(meta: &Meta) -> Result<Binding> {
+    let mut sampler_type = quote!(Filtering);
+    parse_flags(meta, |flag| {
+        sampler_type =
+            match flag.to_string().as_str() {
+                "filtering" => quote!(Filtering),
+                "non_filtering" => quote!(NonFiltering),
+                "comparison" => quote!(Comparison),
+                _ => return Err(Error::new_spanned(
+                    flag,
+                    "unknown sampler option, expected `filtering`, `non_filtering` or `comparison`",
+                )),
+            };
+        Ok(())
+    })?;
+    Ok(Binding::Sampler { sampler_type })
+}
+
+// accepts both `#[attr]` and `#[attr(flag, ...)]`
+fn parse_flags(meta: &Meta, mut f: impl FnMut(&Ident) -> Result<()>) -> Result<()> {
+    match meta {
+        Meta::Path(_) => Ok(()),
+        Meta::List(list) => list.parse_nested_meta(|meta| match meta.path.get_ident() {
+            Some(ident) => f(ident),
+            None => Err(meta.error("expected an identifier")),
+        }),
+        Meta::NameValue(_) => Err(Error::new_spanned(meta, "expected `#[attr(...)]`")),
+    }
+}
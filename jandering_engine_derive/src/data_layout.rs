@@ -0,0 +1,86 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Error, Fields, Ident, Result, Type};
+
+use crate::vertex_layout::is_repr_c;
+
+pub fn expand(input: DeriveInput) -> Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "DataLayout can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "DataLayout can only be derived for structs",
+            ))
+        }
+    };
+
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "DataLayout can not be derived for generic structs",
+        ));
+    }
+
+    if !is_repr_c(&input)? {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "DataLayout needs #[repr(C)] to know the field offsets",
+        ));
+    }
+
+    let shader = quote!(::jandering_engine::shader);
+
+    let mut offsets = Vec::new();
+    let mut layouts = Vec::new();
+    let mut previous: Option<(Ident, &Type)> = None;
+    for (i, field) in fields.iter().enumerate() {
+        let ty = &field.ty;
+        let offset = format_ident!("OFFSET_{}", i);
+
+        offsets.push(match &previous {
+            Some((previous, previous_ty)) => quote! {
+                const #offset: usize = #shader::repr_c_offset(
+                    #previous + ::core::mem::size_of::<#previous_ty>(),
+                    ::core::mem::align_of::<#ty>(),
+                );
+            },
+            None => quote!(const #offset: usize = 0;),
+        });
+        previous = Some((offset.clone(), ty));
+
+        let name = field.ident.as_ref().unwrap().to_string();
+        layouts.push(quote! {
+            #shader::layout::FieldLayout {
+                name: #name.to_string(),
+                offset: #offset as u64,
+                size: ::core::mem::size_of::<#ty>() as u64,
+            }
+        });
+    }
+
+    let name = &input.ident;
+    let name_string = name.to_string();
+
+    Ok(quote! {
+        impl #shader::layout::DataLayout for #name {
+            fn data_layout() -> #shader::layout::StructLayout {
+                #(#offsets)*
+
+                #shader::layout::StructLayout {
+                    name: #name_string.to_string(),
+                    size: ::core::mem::size_of::<#name>() as u64,
+                    fields: ::std::vec![#(#layouts),*],
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,43 @@
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+mod bind_group;
+mod data_layout;
+mod shader_type;
+mod vertex_layout;
+
+// bindings are numbered in field declaration order, fields without an attribute are skipped
+#[proc_macro_derive(BindGroup, attributes(uniform, storage, texture, sampler))]
+pub fn derive_bind_group(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    bind_group::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+// fields map to consecutive locations, Mat4 takes four of them
+#[proc_macro_derive(VertexLayout, attributes(layout))]
+pub fn derive_vertex_layout(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    vertex_layout::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+// describes a #[repr(C)] struct so typed buffers can be checked against the shader
+#[proc_macro_derive(DataLayout)]
+pub fn derive_data_layout(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    data_layout::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+// lays fields out by wgsl's rules instead of rust's, every field has to be a ShaderType
+#[proc_macro_derive(ShaderType)]
+pub fn derive_shader_type(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    shader_type::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,91 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, Data, DeriveInput, Error, Fields, Result};
+
+pub fn expand(input: DeriveInput) -> Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "ShaderType can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "ShaderType can only be derived for structs",
+            ))
+        }
+    };
+
+    let layout = quote!(::jandering_engine::shader::layout);
+
+    let types = fields.iter().map(|e| &e.ty).collect::<Vec<_>>();
+    let idents = fields.iter().map(|e| &e.ident).collect::<Vec<_>>();
+    let names = idents
+        .iter()
+        .map(|e| e.as_ref().unwrap().to_string())
+        .collect::<Vec<_>>();
+
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#layout::ShaderType));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let name = &input.ident;
+    let name_string = name.to_string();
+
+    // members go at the end of the previous one rounded up to their alignment, the struct
+    // is as big as the end of its last member rounded up to its own alignment
+    Ok(quote! {
+        impl #impl_generics #layout::ShaderType for #name #ty_generics #where_clause {
+            fn align(rules: #layout::LayoutRules) -> u64 {
+                let align = 1u64 #(.max(<#types as #layout::ShaderType>::align(rules)))*;
+                rules.struct_align(align)
+            }
+
+            fn size(rules: #layout::LayoutRules) -> u64 {
+                let mut offset = 0;
+                #(
+                    offset = #layout::round_up(offset, <#types as #layout::ShaderType>::align(rules))
+                        + <#types as #layout::ShaderType>::size(rules);
+                )*
+                #layout::round_up(offset, <Self as #layout::ShaderType>::align(rules))
+            }
+
+            fn write(&self, rules: #layout::LayoutRules, bytes: &mut [u8]) {
+                let mut offset = 0;
+                #(
+                    offset = #layout::round_up(offset, <#types as #layout::ShaderType>::align(rules));
+                    #layout::ShaderType::write(&self.#idents, rules, &mut bytes[offset as usize..]);
+                    offset += <#types as #layout::ShaderType>::size(rules);
+                )*
+                let _ = offset;
+            }
+
+            fn shader_layout(rules: #layout::LayoutRules) -> #layout::StructLayout {
+                let mut fields = ::std::vec::Vec::new();
+                let mut offset = 0;
+                #(
+                    offset = #layout::round_up(offset, <#types as #layout::ShaderType>::align(rules));
+                    fields.push(#layout::FieldLayout {
+                        name: #names.to_string(),
+                        offset,
+                        size: <#types as #layout::ShaderType>::size(rules),
+                    });
+                    offset += <#types as #layout::ShaderType>::size(rules);
+                )*
+                let _ = offset;
+
+                #layout::StructLayout {
+                    name: #name_string.to_string(),
+                    size: <Self as #layout::ShaderType>::size(rules),
+                    fields,
+                }
+            }
+        }
+    })
+}
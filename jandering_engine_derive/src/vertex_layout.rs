@@ -0,0 +1,279 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Error, Expr, Fields, Ident, Lit, Result, Type};
+
+struct Layout {
+    instance: bool,
+    start_location: u32,
+}
+
+// a field becomes `count` attributes of `format`, each `stride` bytes after the previous
+struct Columns {
+    format: Ident,
+    count: u32,
+    stride: u64,
+}
+
+pub fn expand(input: DeriveInput) -> Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "VertexLayout can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "VertexLayout can only be derived for structs",
+            ))
+        }
+    };
+
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "VertexLayout can not be derived for generic structs",
+        ));
+    }
+
+    if !is_repr_c(&input)? {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "VertexLayout needs #[repr(C)] to know the field offsets",
+        ));
+    }
+
+    let layout = parse_layout(&input)?;
+    let path = quote!(::jandering_engine::shader);
+
+    let mut offsets = Vec::new();
+    let mut entries = Vec::new();
+    let mut location = layout.start_location;
+    let mut previous: Option<(Ident, &Type)> = None;
+    for (i, field) in fields.iter().enumerate() {
+        let ty = &field.ty;
+        let offset = format_ident!("OFFSET_{}", i);
+
+        // repr(C) places every field at the end of the previous one, aligned up
+        offsets.push(match &previous {
+            Some((previous, previous_ty)) => quote! {
+                const #offset: usize = #path::repr_c_offset(
+                    #previous + ::core::mem::size_of::<#previous_ty>(),
+                    ::core::mem::align_of::<#ty>(),
+                );
+            },
+            None => quote!(const #offset: usize = 0;),
+        });
+        previous = Some((offset.clone(), ty));
+
+        let Some(Columns {
+            format,
+            count,
+            stride,
+        }) = parse_field(field)?
+        else {
+            continue;
+        };
+
+        for column in 0..count as u64 {
+            let column_offset = column * stride;
+            entries.push(quote! {
+                #path::BufferLayoutEntry {
+                    location: #location,
+                    data_type: #path::BufferLayoutEntryDataType::#format,
+                    offset: Some(#offset as u64 + #column_offset),
+                }
+            });
+            location += 1;
+        }
+    }
+
+    let name = &input.ident;
+    let step_mode = if layout.instance {
+        quote!(Instance)
+    } else {
+        quote!(Vertex)
+    };
+
+    Ok(quote! {
+        impl #path::VertexLayout for #name {
+            fn desc() -> #path::BufferLayout {
+                #(#offsets)*
+
+                #path::BufferLayout {
+                    step_mode: #path::BufferLayoutStepMode::#step_mode,
+                    entries: ::std::vec![#(#entries),*],
+                    stride: Some(::core::mem::size_of::<#name>() as u64),
+                }
+            }
+        }
+    })
+}
+
+pub fn is_repr_c(input: &DeriveInput) -> Result<bool> {
+    let mut repr_c = false;
+    for attr in input.attrs.iter().filter(|e| e.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") {
+                repr_c = true;
+            }
+            // skip arguments like align(16)
+            if meta.input.peek(syn::token::Paren) {
+                let _content;
+                syn::parenthesized!(_content in meta.input);
+            }
+            Ok(())
+        })?;
+    }
+    Ok(repr_c)
+}
+
+fn parse_layout(input: &DeriveInput) -> Result<Layout> {
+    let mut layout = Layout {
+        instance: false,
+        start_location: 0,
+    };
+
+    for attr in input.attrs.iter().filter(|e| e.path().is_ident("layout")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("step") {
+                let step: Ident = meta.value()?.parse()?;
+                layout.instance = match step.to_string().as_str() {
+                    "vertex" => false,
+                    "instance" => true,
+                    _ => {
+                        return Err(Error::new_spanned(
+                            step,
+                            "unknown step, expected `vertex` or `instance`",
+                        ))
+                    }
+                };
+                Ok(())
+            } else if meta.path.is_ident("start_location") {
+                let location: syn::LitInt = meta.value()?.parse()?;
+                layout.start_location = location.base10_parse()?;
+                Ok(())
+            } else {
+                Err(meta.error("unknown layout option, expected `step` or `start_location`"))
+            }
+        })?;
+    }
+
+    Ok(layout)
+}
+
+fn parse_field(field: &syn::Field) -> Result<Option<Columns>> {
+    let mut format = None;
+    for attr in field.attrs.iter().filter(|e| e.path().is_ident("layout")) {
+        let mut skip = false;
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                skip = true;
+                Ok(())
+            } else if meta.path.is_ident("format") {
+                format = Some(meta.value()?.parse::<Ident>()?);
+                Ok(())
+            } else {
+                Err(meta.error("unknown field option, expected `skip` or `format`"))
+            }
+        })?;
+
+        if skip {
+            return Ok(None);
+        }
+    }
+
+    if let Some(format) = format {
+        return Ok(Some(Columns {
+            format,
+            count: 1,
+            stride: 0,
+        }));
+    }
+
+    match columns_for_type(&field.ty) {
+        Some(columns) => Ok(Some(columns)),
+        None => Err(Error::new_spanned(
+            &field.ty,
+            "unsupported vertex field type, use #[layout(format = ...)] or #[layout(skip)]",
+        )),
+    }
+}
+
+fn columns_for_type(ty: &Type) -> Option<Columns> {
+    let columns = |format: &str, count: u32, stride: u64| {
+        Some(Columns {
+            format: format_ident!("{}", format),
+            count,
+            stride,
+        })
+    };
+
+    match ty {
+        Type::Path(path) => {
+            let ident = path.path.segments.last()?.ident.to_string();
+            match ident.as_str() {
+                "f32" => columns("Float32", 1, 0),
+                "u32" => columns("U32", 1, 0),
+                "i32" => columns("Sint32", 1, 0),
+                "Vec2" => columns("Float32x2", 1, 0),
+                "Vec3" | "Vec3A" => columns("Float32x3", 1, 0),
+                "Vec4" | "Quat" => columns("Float32x4", 1, 0),
+                "UVec2" => columns("Uint32x2", 1, 0),
+                "UVec3" => columns("Uint32x3", 1, 0),
+                "UVec4" => columns("Uint32x4", 1, 0),
+                "IVec2" => columns("Sint32x2", 1, 0),
+                "IVec3" => columns("Sint32x3", 1, 0),
+                "IVec4" => columns("Sint32x4", 1, 0),
+                "Mat2" => columns("Float32x2", 2, 8),
+                "Mat3" => columns("Float32x3", 3, 12),
+                "Mat4" => columns("Float32x4", 4, 16),
+                _ => None,
+            }
+        }
+        Type::Array(array) => {
+            let Expr::Lit(syn::ExprLit {
+                lit: Lit::Int(len), ..
+            }) = &array.len
+            else {
+                return None;
+            };
+            let len: u32 = len.base10_parse().ok()?;
+
+            let Type::Path(element) = array.elem.as_ref() else {
+                return None;
+            };
+            let element = element.path.get_ident()?.to_string();
+
+            let format = match (element.as_str(), len) {
+                ("f32", 1) => "Float32",
+                ("f32", 2) => "Float32x2",
+                ("f32", 3) => "Float32x3",
+                ("f32", 4) => "Float32x4",
+                ("u32", 1) => "U32",
+                ("u32", 2) => "Uint32x2",
+                ("u32", 3) => "Uint32x3",
+                ("u32", 4) => "Uint32x4",
+                ("i32", 1) => "Sint32",
+                ("i32", 2) => "Sint32x2",
+                ("i32", 3) => "Sint32x3",
+                ("i32", 4) => "Sint32x4",
+                ("u16", 2) => "Uint16x2",
+                ("u16", 4) => "Uint16x4",
+                ("i16", 2) => "Sint16x2",
+                ("i16", 4) => "Sint16x4",
+                ("u8", 2) => "Uint8x2",
+                ("u8", 4) => "Uint8x4",
+                ("i8", 2) => "Sint8x2",
+                ("i8", 4) => "Sint8x4",
+                _ => return None,
+            };
+            columns(format, 1, 0)
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,9 @@
+// matches the data MatrixCamera uploads
+struct Camera {
+    up: vec3<f32>,
+    right: vec3<f32>,
+    position: vec3<f32>,
+    direction: vec3<f32>,
+    view_proj: mat4x4<f32>,
+    inverse_view: mat4x4<f32>,
+};
@@ -0,0 +1,30 @@
+// matches object::Instance
+struct InstanceInput{
+    @location(5) model_matrix_0: vec4<f32>,
+    @location(6) model_matrix_1: vec4<f32>,
+    @location(7) model_matrix_2: vec4<f32>,
+    @location(8) model_matrix_3: vec4<f32>,
+
+    @location(9)  inv_model_matrix_0: vec4<f32>,
+    @location(10) inv_model_matrix_1: vec4<f32>,
+    @location(11) inv_model_matrix_2: vec4<f32>,
+    @location(12) inv_model_matrix_3: vec4<f32>,
+}
+
+fn instance_model_matrix(instance: InstanceInput) -> mat4x4<f32> {
+    return mat4x4<f32>(
+        instance.model_matrix_0,
+        instance.model_matrix_1,
+        instance.model_matrix_2,
+        instance.model_matrix_3,
+    );
+}
+
+fn instance_inv_model_matrix(instance: InstanceInput) -> mat4x4<f32> {
+    return mat4x4<f32>(
+        instance.inv_model_matrix_0,
+        instance.inv_model_matrix_1,
+        instance.inv_model_matrix_2,
+        instance.inv_model_matrix_3,
+    );
+}
@@ -0,0 +1,6 @@
+// matches object::Vertex
+struct VertexInput{
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+};
@@ -0,0 +1,73 @@
+// for build scripts, `je_shader_build::prepare_shaders("res/shaders")` in build.rs fails the
+// build on broken shaders instead of the first run that loads them. it's its own crate so
+// build-dependencies don't pull in wgpu and winit
+use std::path::{Path, PathBuf};
+
+use naga::valid::{Capabilities, ValidationFlags, Validator};
+
+use preprocessor::{is_builtin_include, preprocess};
+
+pub mod preprocessor;
+
+// checks every .wgsl file in `dir` and writes it with its includes resolved to OUT_DIR, so it can
+// be embedded with include_str!(concat!(env!("OUT_DIR"), "/name.wgsl")). shaders with features
+// are checked with all of them turned off
+pub fn prepare_shaders(dir: impl AsRef<Path>) {
+    let dir = dir.as_ref();
+    let out_dir = std::env::var_os("OUT_DIR").map(PathBuf::from);
+    println!("cargo:rerun-if-changed={}", dir.display());
+
+    let entries = std::fs::read_dir(dir)
+        .unwrap_or_else(|err| panic!("can't read shader directory {}: {}", dir.display(), err));
+
+    let mut errors = Vec::new();
+    for entry in entries {
+        let path = entry.unwrap().path();
+        if path.extension().map_or(true, |e| e != "wgsl") {
+            continue;
+        }
+
+        match prepare_shader(&path) {
+            Ok(code) => {
+                if let Some(out_dir) = &out_dir {
+                    let out = out_dir.join(path.file_name().unwrap());
+                    std::fs::write(&out, code)
+                        .unwrap_or_else(|err| panic!("can't write {}: {}", out.display(), err));
+                }
+            }
+            Err(err) => errors.push(err),
+        }
+    }
+
+    if !errors.is_empty() {
+        panic!("{}", errors.join("\n"));
+    }
+}
+
+// the shader with its includes resolved, includes are looked up in res/ like at runtime
+pub fn prepare_shader(path: &Path) -> Result<String, String> {
+    let name = path.display().to_string();
+    println!("cargo:rerun-if-changed={name}");
+
+    let code = std::fs::read_to_string(path).map_err(|err| format!("{name}: {err}"))?;
+    let shader = preprocess(&code, &name, &[]).map_err(|err| err.to_string())?;
+
+    for include in shader.files().iter().skip(1) {
+        if !is_builtin_include(include) {
+            println!("cargo:rerun-if-changed=res/{include}");
+        }
+    }
+
+    let module = shader.parse().map_err(|err| err.to_string())?;
+    Validator::new(ValidationFlags::all(), Capabilities::all())
+        .validate(&module)
+        .map_err(|err| {
+            let line = err
+                .location(&shader.code)
+                .map_or(1, |e| e.line_number as usize);
+            let (file, line) = shader.source_location(line).unwrap_or((&name, line));
+            format!("{file}:{line}: {}", err.as_inner())
+        })?;
+
+    Ok(shader.code)
+}
@@ -0,0 +1,427 @@
+use std::collections::{HashMap, HashSet};
+
+// shared wgsl the engine ships, `#include "jandering_engine/camera.wgsl"`
+const BUILTIN_INCLUDES: &[(&str, &str)] = &[
+    (
+        "jandering_engine/camera.wgsl",
+        include_str!("include/camera.wgsl"),
+    ),
+    (
+        "jandering_engine/vertex.wgsl",
+        include_str!("include/vertex.wgsl"),
+    ),
+    (
+        "jandering_engine/instance.wgsl",
+        include_str!("include/instance.wgsl"),
+    ),
+];
+
+#[derive(Debug, Clone)]
+pub enum PreprocessError {
+    Include {
+        file: String,
+        line: usize,
+        include: String,
+        error: String,
+    },
+    Directive {
+        file: String,
+        line: usize,
+        message: String,
+    },
+    UnterminatedIf {
+        file: String,
+        line: usize,
+    },
+    Parse {
+        file: String,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+impl std::fmt::Display for PreprocessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PreprocessError::Include {
+                file,
+                line,
+                include,
+                error,
+            } => write!(f, "{file}:{line}: can't include \"{include}\": {error}"),
+            PreprocessError::Directive {
+                file,
+                line,
+                message,
+            } => write!(f, "{file}:{line}: {message}"),
+            PreprocessError::UnterminatedIf { file, line } => {
+                write!(f, "{file}:{line}: #ifdef is never closed with #endif")
+            }
+            PreprocessError::Parse {
+                file,
+                line,
+                column,
+                message,
+            } => write!(f, "{file}:{line}:{column}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for PreprocessError {}
+
+pub struct PreprocessedShader {
+    pub code: String,
+    files: Vec<String>,
+    // file index and 1-based line each line of `code` came from
+    lines: Vec<(usize, usize)>,
+}
+
+impl PreprocessedShader {
+    // the shader's own file first, then everything it included
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+
+    pub fn source_location(&self, line: usize) -> Option<(&str, usize)> {
+        let (file, line) = self.lines.get(line.checked_sub(1)?)?;
+        Some((&self.files[*file], *line))
+    }
+
+    pub fn parse(&self) -> Result<naga::Module, PreprocessError> {
+        naga::front::wgsl::parse_str(&self.code).map_err(|e| {
+            let (line, column) = e
+                .location(&self.code)
+                .map(|e| (e.line_number as usize, e.line_position as usize))
+                .unwrap_or((1, 1));
+            let (file, line) = self.source_location(line).unwrap_or(("", line));
+            PreprocessError::Parse {
+                file: file.to_string(),
+                line,
+                column,
+                message: e.message().to_string(),
+            }
+        })
+    }
+}
+
+struct Condition {
+    active: bool,
+    seen_else: bool,
+    line: usize,
+}
+
+struct Preprocessor {
+    defines: HashMap<String, String>,
+    included: HashSet<String>,
+    code: String,
+    files: Vec<String>,
+    lines: Vec<(usize, usize)>,
+}
+
+// resolves #include, #define, #undef, #ifdef, #ifndef, #else and #endif, every file is
+// included once so shared structs don't get redefined. on the web files load asynchronously,
+// so only the built-in includes resolve at runtime, shaders including their own files have to
+// be resolved at build time with prepare_shaders
+pub fn preprocess(
+    source: &str,
+    file: &str,
+    defines: &[(String, String)],
+) -> Result<PreprocessedShader, PreprocessError> {
+    let mut preprocessor = Preprocessor {
+        defines: defines.iter().cloned().collect(),
+        included: HashSet::new(),
+        code: String::new(),
+        files: Vec::new(),
+        lines: Vec::new(),
+    };
+    preprocessor.process(source, file)?;
+
+    Ok(PreprocessedShader {
+        code: preprocessor.code,
+        files: preprocessor.files,
+        lines: preprocessor.lines,
+    })
+}
+
+impl Preprocessor {
+    fn process(&mut self, source: &str, file: &str) -> Result<(), PreprocessError> {
+        let file_index = self.files.len();
+        self.files.push(file.to_string());
+
+        let mut conditions: Vec<Condition> = Vec::new();
+
+        for (i, line) in source.lines().enumerate() {
+            let line_number = i + 1;
+            let active = conditions.iter().all(|e| e.active);
+            let directive_error = |message: String| PreprocessError::Directive {
+                file: file.to_string(),
+                line: line_number,
+                message,
+            };
+
+            let trimmed = line.trim();
+            let Some(directive) = trimmed.strip_prefix('#') else {
+                if active {
+                    let line = self.substitute(line);
+                    self.code.push_str(&line);
+                    self.code.push('\n');
+                    self.lines.push((file_index, line_number));
+                }
+                continue;
+            };
+
+            let (name, argument) = match directive.split_once(char::is_whitespace) {
+                Some((name, argument)) => (name, argument.trim()),
+                None => (directive, ""),
+            };
+
+            match name {
+                "ifdef" | "ifndef" => {
+                    let defined = self.defines.contains_key(argument);
+                    conditions.push(Condition {
+                        active: defined == (name == "ifdef"),
+                        seen_else: false,
+                        line: line_number,
+                    });
+                }
+                "else" => {
+                    let condition = conditions
+                        .last_mut()
+                        .filter(|e| !e.seen_else)
+                        .ok_or_else(|| directive_error("#else without #ifdef".to_string()))?;
+                    condition.active = !condition.active;
+                    condition.seen_else = true;
+                }
+                "endif" => {
+                    conditions
+                        .pop()
+                        .ok_or_else(|| directive_error("#endif without #ifdef".to_string()))?;
+                }
+                _ if !active => {}
+                "define" => {
+                    let (define, value) = match argument.split_once(char::is_whitespace) {
+                        Some((define, value)) => (define, value.trim()),
+                        None => (argument, ""),
+                    };
+                    if define.is_empty() {
+                        return Err(directive_error("#define needs a name".to_string()));
+                    }
+                    self.defines.insert(define.to_string(), value.to_string());
+                }
+                "undef" => {
+                    self.defines.remove(argument);
+                }
+                "include" => {
+                    let include = argument
+                        .strip_prefix('"')
+                        .and_then(|e| e.strip_suffix('"'))
+                        .ok_or_else(|| {
+                            directive_error(format!(
+                                "expected #include \"file.wgsl\", got #include {argument}"
+                            ))
+                        })?;
+
+                    if self.included.insert(include.to_string()) {
+                        let code =
+                            load_include(include).map_err(|error| PreprocessError::Include {
+                                file: file.to_string(),
+                                line: line_number,
+                                include: include.to_string(),
+                                error,
+                            })?;
+                        self.process(&code, include)?;
+                    }
+                }
+                _ => return Err(directive_error(format!("unknown directive #{name}"))),
+            }
+        }
+
+        match conditions.last() {
+            Some(condition) => Err(PreprocessError::UnterminatedIf {
+                file: file.to_string(),
+                line: condition.line,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    // replaces whole identifiers that have a value defined
+    fn substitute(&self, line: &str) -> String {
+        if self.defines.values().all(|e| e.is_empty()) {
+            return line.to_string();
+        }
+
+        let mut result = String::with_capacity(line.len());
+        let mut identifier = String::new();
+        for c in line.chars().chain(std::iter::once('\n')) {
+            if c.is_alphanumeric() || c == '_' {
+                identifier.push(c);
+                continue;
+            }
+
+            match self.defines.get(&identifier) {
+                Some(value) if !value.is_empty() => result.push_str(value),
+                _ => result.push_str(&identifier),
+            }
+            identifier.clear();
+
+            if c != '\n' {
+                result.push(c);
+            }
+        }
+        result
+    }
+}
+
+pub fn is_builtin_include(name: &str) -> bool {
+    BUILTIN_INCLUDES.iter().any(|(e, _)| *e == name)
+}
+
+fn load_include(name: &str) -> Result<String, String> {
+    if let Some((_, code)) = BUILTIN_INCLUDES.iter().find(|(e, _)| *e == name) {
+        return Ok(code.to_string());
+    }
+
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            Err(
+                "only built-in includes are available on the web, resolve the rest at build time \
+                 with je_shader_build::prepare_shaders"
+                    .to_string(),
+            )
+        } else {
+            // same place the engine loads files from
+            std::fs::read_to_string(std::path::Path::new("res").join(name))
+                .map_err(|e| e.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(shader: &PreprocessedShader) -> Vec<&str> {
+        shader.code.lines().map(str::trim).collect()
+    }
+
+    #[test]
+    fn nested_conditions() {
+        let source = "\
+#ifdef A
+a
+#ifdef B
+ab
+#else
+a_not_b
+#endif
+#else
+not_a
+#ifndef B
+not_a_not_b
+#endif
+#endif
+end";
+        let defines = [("A".to_string(), String::new())];
+        let shader = preprocess(source, "main.wgsl", &defines).unwrap();
+        assert_eq!(lines(&shader), ["a", "a_not_b", "end"]);
+
+        let shader = preprocess(source, "main.wgsl", &[]).unwrap();
+        assert_eq!(lines(&shader), ["not_a", "not_a_not_b", "end"]);
+    }
+
+    #[test]
+    fn defines_inside_inactive_blocks_are_skipped() {
+        let source = "\
+#ifdef A
+#define B
+#endif
+#ifdef B
+b
+#endif";
+        let shader = preprocess(source, "main.wgsl", &[]).unwrap();
+        assert!(shader.code.is_empty());
+    }
+
+    #[test]
+    fn includes_once() {
+        let source = "\
+#include \"jandering_engine/vertex.wgsl\"
+#include \"jandering_engine/vertex.wgsl\"
+fn f() {}";
+        let shader = preprocess(source, "main.wgsl", &[]).unwrap();
+        assert_eq!(shader.code.matches("struct VertexInput").count(), 1);
+        assert_eq!(
+            shader.files(),
+            ["main.wgsl", "jandering_engine/vertex.wgsl"]
+        );
+    }
+
+    #[test]
+    fn substitutes_whole_identifiers() {
+        let source = "\
+#define COUNT 4
+var<private> a: array<f32, COUNT>;
+var<private> COUNT_2: f32;";
+        let shader = preprocess(source, "main.wgsl", &[]).unwrap();
+        assert_eq!(
+            lines(&shader),
+            [
+                "var<private> a: array<f32, 4>;",
+                "var<private> COUNT_2: f32;"
+            ]
+        );
+
+        let defines = [("COUNT".to_string(), "8".to_string())];
+        let shader = preprocess("let a = COUNT;", "main.wgsl", &defines).unwrap();
+        assert_eq!(lines(&shader), ["let a = 8;"]);
+    }
+
+    #[test]
+    fn maps_lines_to_their_files() {
+        let source = "\
+#ifdef A
+skipped
+#endif
+#include \"jandering_engine/vertex.wgsl\"
+fn f() -> f32 {
+    return 1.0
+}";
+        let shader = preprocess(source, "main.wgsl", &[]).unwrap();
+        // the include's first line, then the line after the include
+        assert_eq!(
+            shader.source_location(1),
+            Some(("jandering_engine/vertex.wgsl", 1))
+        );
+        assert_eq!(shader.source_location(7), Some(("main.wgsl", 5)));
+
+        match shader.parse() {
+            Err(PreprocessError::Parse { file, line, .. }) => {
+                assert_eq!(file, "main.wgsl");
+                // naga reports the missing semicolon at the closing brace
+                assert_eq!(line, 7);
+            }
+            _ => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn reports_directive_errors_where_they_are() {
+        let err = preprocess("a\n#ifdef A\nb", "main.wgsl", &[])
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "main.wgsl:2: #ifdef is never closed with #endif"
+        );
+
+        let err = preprocess("#endif", "main.wgsl", &[]).err().unwrap();
+        assert_eq!(err.to_string(), "main.wgsl:1: #endif without #ifdef");
+
+        let err = preprocess("#include \"missing.wgsl", "main.wgsl", &[])
+            .err()
+            .unwrap();
+        assert!(matches!(err, PreprocessError::Directive { line: 1, .. }));
+    }
+}
@@ -1,10 +1,11 @@
-use winit_window::WinitWindow;
+use winit_window::{WinitSharedWindow, WinitWindow};
 use winit_window_manager::WinitWindowManager;
 
 pub mod winit_window;
 pub mod winit_window_manager;
 
 pub type Window = WinitWindow;
+pub type SharedWindow = WinitSharedWindow;
 pub type WindowManager = WinitWindowManager;
 
 pub type WindowId = u32;
@@ -16,6 +17,9 @@ pub trait WindowManagerTrait {
     fn spawn_window(&mut self, config: WindowConfig) -> Window;
 
     fn end(&mut self);
+
+    // ids of the windows that closed since the last call, their surfaces can be dropped
+    fn take_closed_windows(&mut self) -> Vec<WindowId>;
 }
 
 pub trait WindowTrait {
@@ -75,9 +79,12 @@ pub trait WindowTrait {
 
     fn events(&self) -> &Events;
 
-    fn get_window_handle(&self) -> raw_window_handle::WindowHandle;
+    // keeps the os window alive while it's held, what surfaces get created from
+    fn shared(&self) -> SharedWindow;
+
+    fn get_window_handle(&self) -> raw_window_handle::WindowHandle<'_>;
 
-    fn get_display_handle(&self) -> raw_window_handle::DisplayHandle;
+    fn get_display_handle(&self) -> raw_window_handle::DisplayHandle<'_>;
 
     fn should_close(&self) -> bool;
 
@@ -253,7 +260,7 @@ pub enum WindowResolution {
     Auto,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum FpsPreference {
     Vsync,
     Exact(u32),
@@ -407,7 +414,7 @@ impl Events {
         self.events.push(event)
     }
 
-    pub fn iter(&self) -> std::slice::Iter<WindowEvent> {
+    pub fn iter(&self) -> std::slice::Iter<'_, WindowEvent> {
         self.events.iter()
     }
 
@@ -82,7 +82,7 @@ impl WindowTrait for WinitWindow {
     fn close(&mut self) {
         let mut window = self.inner_window.lock().unwrap();
         match &mut *window {
-            InnerWinitWindow::Initialized { should_close, .. } => *should_close = false,
+            InnerWinitWindow::Initialized { should_close, .. } => *should_close = true,
             InnerWinitWindow::Uninitalized { .. } => {
                 panic!("Attempting to close uninitialized window")
             }
@@ -314,7 +314,13 @@ impl WindowTrait for WinitWindow {
         }
     }
 
-    fn get_window_handle(&self) -> raw_window_handle::WindowHandle {
+    fn shared(&self) -> WinitSharedWindow {
+        WinitSharedWindow {
+            inner_window: self.inner_window.clone(),
+        }
+    }
+
+    fn get_window_handle(&self) -> raw_window_handle::WindowHandle<'_> {
         use raw_window_handle::WindowHandle;
 
         let mut window = self.inner_window.lock().unwrap();
@@ -330,7 +336,7 @@ impl WindowTrait for WinitWindow {
         }
     }
 
-    fn get_display_handle(&self) -> raw_window_handle::DisplayHandle {
+    fn get_display_handle(&self) -> raw_window_handle::DisplayHandle<'_> {
         use raw_window_handle::DisplayHandle;
 
         let mut window = self.inner_window.lock().unwrap();
@@ -398,6 +404,46 @@ impl WindowTrait for WinitWindow {
     }
 }
 
+// keeps the os window alive for as long as it's held, so a surface created from it can't
+// outlive the window it draws to
+#[derive(Debug, Clone)]
+pub struct WinitSharedWindow {
+    inner_window: Arc<Mutex<InnerWinitWindow>>,
+}
+
+impl HasWindowHandle for WinitSharedWindow {
+    fn window_handle(
+        &self,
+    ) -> Result<raw_window_handle::WindowHandle<'_>, raw_window_handle::HandleError> {
+        let window = self.inner_window.lock().unwrap();
+        match &*window {
+            // an initialized window is never replaced, so the handle stays valid while self lives
+            InnerWinitWindow::Initialized { window, .. } => Ok(unsafe {
+                raw_window_handle::WindowHandle::borrow_raw(window.window_handle()?.as_raw())
+            }),
+            InnerWinitWindow::Uninitalized { .. } => {
+                Err(raw_window_handle::HandleError::Unavailable)
+            }
+        }
+    }
+}
+
+impl HasDisplayHandle for WinitSharedWindow {
+    fn display_handle(
+        &self,
+    ) -> Result<raw_window_handle::DisplayHandle<'_>, raw_window_handle::HandleError> {
+        let window = self.inner_window.lock().unwrap();
+        match &*window {
+            InnerWinitWindow::Initialized { window, .. } => Ok(unsafe {
+                raw_window_handle::DisplayHandle::borrow_raw(window.display_handle()?.as_raw())
+            }),
+            InnerWinitWindow::Uninitalized { .. } => {
+                Err(raw_window_handle::HandleError::Unavailable)
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 #[allow(dead_code)]
 pub(crate) enum InnerWinitWindow {
@@ -23,6 +23,7 @@ pub struct WinitWindowManager {
     windows: HashMap<WindowId, Arc<Mutex<InnerWinitWindow>>>,
     queued_windows: HashMap<WindowId, Arc<Mutex<InnerWinitWindow>>>,
     ids_to_handles: HashMap<winit::window::WindowId, WindowId>,
+    closed_windows: Vec<WindowId>,
 }
 
 impl WindowManagerTrait for WinitWindowManager {
@@ -35,6 +36,7 @@ impl WindowManagerTrait for WinitWindowManager {
             windows: HashMap::new(),
             queued_windows: HashMap::new(),
             ids_to_handles: HashMap::new(),
+            closed_windows: Vec::new(),
         }
     }
 
@@ -71,6 +73,10 @@ impl WindowManagerTrait for WinitWindowManager {
     fn end(&mut self) {
         self.should_end = true;
     }
+
+    fn take_closed_windows(&mut self) -> Vec<WindowId> {
+        std::mem::take(&mut self.closed_windows)
+    }
 }
 
 struct WinitEventHandler<F: FnMut(&mut WinitWindowManager)> {
@@ -262,13 +268,31 @@ impl<F: FnMut(&mut WinitWindowManager)> ApplicationHandler<()> for WinitEventHan
 
         self.create_queued_windows(event_loop);
 
-        self.window_manager.windows.retain(|_, window| {
+        let closed_windows = &mut self.window_manager.closed_windows;
+        self.window_manager.windows.retain(|id, window| {
             let window = window.lock().unwrap();
-            match &*window {
-                InnerWinitWindow::Initialized { should_close, .. } => !should_close,
+            let keep = match &*window {
+                InnerWinitWindow::Initialized {
+                    should_close,
+                    window,
+                    ..
+                } => {
+                    // the os window lives on until every handle to it is dropped
+                    if *should_close {
+                        window.set_visible(false);
+                    }
+                    !should_close
+                }
                 InnerWinitWindow::Uninitalized { .. } => false,
+            };
+            if !keep {
+                closed_windows.push(*id);
             }
+            keep
         });
+        self.window_manager
+            .ids_to_handles
+            .retain(|_, id| !closed_windows.contains(id));
 
         if self.window_manager.windows.is_empty() {
             event_loop.exit();
@@ -37,8 +37,8 @@ use jandering_engine::{
     object::{Instance, Object, Vertex},
     renderer::Janderer,
     shader::ShaderDescriptor,
-    texture::{texture_usage::{self}, TextureDescriptor, TextureFormat},
-    types::{UVec2, Vec3},
+    texture::{texture_usage::{self}, TextureDescriptor, TextureFormat, TextureSize},
+    types::Vec3,
     window::{WindowConfig, WindowEvent, WindowManagerTrait, WindowTrait},
 };
 
@@ -50,7 +50,7 @@ const CAMEREA_FAR: f32 = 100000.0;
 
 #[wasm_bindgen(start)]
 async fn main() {
-    let mut engine = Engine::default().await;
+    let mut engine = Engine::default().await.unwrap();
 
     let resolution = 512;
 
@@ -74,7 +74,7 @@ async fn main() {
             .with_bind_group_layouts(vec![camera.get_layout()])
             .with_depth(true)
             .with_backface_culling(false),
-    );
+    ).unwrap();
 
     let camera_handle = renderer.create_typed_bind_group(camera);
 
@@ -95,7 +95,7 @@ async fn main() {
     );
 
     let depth_texture = renderer.create_texture(TextureDescriptor {
-        size: UVec2::splat(resolution),
+        size: TextureSize::Screen(window.id(), 1.0),
         format: TextureFormat::Depth32F,
         usage: texture_usage::ALL,
         ..Default::default()
@@ -111,7 +111,7 @@ async fn main() {
 
         for event in window.events().iter() {
             match event{
-                WindowEvent::WindowInitialized => renderer.register_window(&window),
+                WindowEvent::WindowInitialized => renderer.register_window(&window).unwrap(),
                 _=>{}
             }
         }
@@ -132,15 +132,6 @@ async fn main() {
                 CAMEREA_NEAR,
                 CAMEREA_FAR,
             );
-            renderer.re_create_texture(
-                TextureDescriptor {
-                    size: window.size().into(),
-                    format: TextureFormat::Depth32F,
-                    usage: texture_usage::ALL,
-                    ..Default::default()
-                },
-                depth_texture,
-            );
         }
 
         cube.instances.iter_mut().for_each(|e| {